@@ -0,0 +1,338 @@
+pub mod adaptive {
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::{DenseOutput, OdeSolver, SolverError, SolverStats};
+
+    const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+    const A: [[f64; 6]; 7] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+        [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+        [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+        [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+        [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0]
+    ];
+    /// Difference between the fifth and fourth order weights, used for the error estimate
+    const E: [f64; 7] = [
+        71.0 / 57600.0,
+        0.0,
+        -71.0 / 16695.0,
+        71.0 / 1920.0,
+        -17253.0 / 339200.0,
+        22.0 / 525.0,
+        -1.0 / 40.0
+    ];
+
+    /// Weights of the fourth order continuous extension of the method
+    const D: [f64; 7] = [
+        -12715105075.0 / 11282082432.0,
+        0.0,
+        87487479700.0 / 32700410799.0,
+        -10690763975.0 / 1880347072.0,
+        701980252875.0 / 199316789632.0,
+        -1453857185.0 / 822651844.0,
+        69997945.0 / 29380423.0
+    ];
+
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 5.0;
+
+    /// Stages of the last accepted step, kept for the continuous extension
+    #[derive(Debug, Clone)]
+    struct AcceptedStep {
+        t: f64,
+        h: f64,
+        state: Vec<f64>,
+        stages: Vec<Vec<f64>>
+    }
+
+    /// Adaptive Dormand-Prince 5(4) embedded Runge-Kutta solver
+    #[derive(Debug, Clone)]
+    pub struct DormandPrince {
+        pub absolute_tolerance: f64,
+        pub relative_tolerance: f64,
+        pub min_step: f64,
+        pub max_step: f64,
+        pub initial_step: Option<f64>,
+        step_size: Option<f64>,
+        last_derivative: Option<(f64, Vec<f64>, Vec<f64>)>,
+        last_step: Option<AcceptedStep>,
+        stats: SolverStats
+    }
+
+    impl DormandPrince {
+        /// Creates a DormandPrince solver
+        ///
+        /// *Params
+        ///
+        /// absolute_tolerance: error allowed in each component regardless of its magnitude
+        ///
+        /// relative_tolerance: error allowed in each component relative to its magnitude
+        pub fn new(absolute_tolerance: f64, relative_tolerance: f64) -> DormandPrince {
+            DormandPrince {
+                absolute_tolerance,
+                relative_tolerance,
+                min_step: 1e-12,
+                max_step: f64::INFINITY,
+                initial_step: None,
+                step_size: None,
+                last_derivative: None,
+                last_step: None,
+                stats: SolverStats::default()
+            }
+        }
+
+        /// Bounds the step sizes the error control may choose
+        pub fn with_step_bounds(mut self, min_step: f64, max_step: f64) -> DormandPrince {
+            self.min_step = min_step;
+            self.max_step = max_step;
+            self
+        }
+
+        /// Sets the size of the first attempted step. When unset it is estimated from the vector field
+        pub fn with_initial_step(mut self, initial_step: f64) -> DormandPrince {
+            self.initial_step = Some(initial_step);
+            self
+        }
+
+        /// Returns the step size the solver will attempt next
+        pub fn step_size(&self) -> Option<f64> {
+            self.step_size
+        }
+
+        fn estimate_initial_step(&self, state: &[f64], derivative: &[f64]) -> f64 {
+            let d0: f64 = self.norm(state, state, state);
+            let d1: f64 = self.norm(derivative, state, state);
+            let h: f64 = if d0 < 1e-5 || d1 < 1e-5 { 1e-6 } else { 0.01 * d0 / d1 };
+            h.clamp(self.min_step, self.max_step)
+        }
+
+        /// Root mean square of the components of v, each scaled by its tolerance
+        fn norm(&self, v: &[f64], state: &[f64], state_next: &[f64]) -> f64 {
+            let sum: f64 = (0..v.len())
+                .map(|i| {
+                    let scale: f64 = self.absolute_tolerance + self.relative_tolerance * state[i].abs().max(state_next[i].abs());
+                    (v[i] / scale).powi(2)
+                })
+                .sum();
+            (sum / v.len().max(1) as f64).sqrt()
+        }
+    }
+
+    impl OdeSolver for DormandPrince {
+        fn step(&mut self, system: &dyn DynamicalSystem, t: f64, state: &[f64], t_end: f64) -> Result<(f64, Vec<f64>), SolverError> {
+            let n: usize = state.len();
+
+            // first same as last: the final stage of the previous step is the first stage of this one
+            let k1: Vec<f64> = match self.last_derivative.take() {
+                Some((t_last, state_last, derivative)) if t_last == t && state_last == state => derivative,
+                _ => {
+                    self.stats.function_evaluations += 1;
+                    system.derivative(t, state)
+                }
+            };
+
+            let mut h: f64 = match self.step_size {
+                Some(h) => h,
+                None => self.initial_step.unwrap_or_else(|| self.estimate_initial_step(state, &k1))
+            };
+
+            loop {
+                h = h.min(self.max_step);
+                let last_step: bool = h >= t_end - t;
+                if last_step {
+                    h = t_end - t;
+                }
+
+                let mut k: Vec<Vec<f64>> = Vec::with_capacity(7);
+                k.push(k1.clone());
+                for stage in 1..7 {
+                    let stage_state: Vec<f64> = (0..n)
+                        .map(|i| state[i] + h * (0..stage).map(|j| A[stage][j] * k[j][i]).sum::<f64>())
+                        .collect();
+                    k.push(system.derivative(t + C[stage] * h, &stage_state));
+                }
+                self.stats.function_evaluations += 6;
+
+                // the seventh stage is evaluated at the fifth order solution
+                let state_next: Vec<f64> = (0..n)
+                    .map(|i| state[i] + h * (0..6).map(|j| A[6][j] * k[j][i]).sum::<f64>())
+                    .collect();
+                let error: Vec<f64> = (0..n).map(|i| h * (0..7).map(|j| E[j] * k[j][i]).sum::<f64>()).collect();
+                let error_norm: f64 = self.norm(&error, state, &state_next);
+
+                if error_norm <= 1.0 {
+                    let factor: f64 = if error_norm == 0.0 { MAX_FACTOR } else { (SAFETY * error_norm.powf(-0.2)).clamp(MIN_FACTOR, MAX_FACTOR) };
+                    self.stats.accepted_steps += 1;
+                    self.step_size = Some((h * factor).clamp(self.min_step, self.max_step));
+                    let t_next: f64 = if last_step { t_end } else { t + h };
+                    self.last_derivative = Some((t_next, state_next.clone(), k[6].clone()));
+                    self.last_step = Some(AcceptedStep {
+                        t,
+                        h,
+                        state: state.to_vec(),
+                        stages: k
+                    });
+                    return Ok((t_next, state_next));
+                }
+
+                self.stats.rejected_steps += 1;
+                h *= (SAFETY * error_norm.powf(-0.2)).max(MIN_FACTOR);
+                if h < self.min_step {
+                    return Err(SolverError::StepSizeTooSmall { t, step: h });
+                }
+            }
+        }
+
+        fn stats(&self) -> SolverStats {
+            self.stats
+        }
+
+        fn reset(&mut self) {
+            self.step_size = None;
+            self.last_derivative = None;
+            self.last_step = None;
+            self.stats = SolverStats::default();
+        }
+
+        /// Uses the fourth order continuous extension of the last accepted step, falling back
+        /// to Hermite interpolation for any other step
+        fn dense_output(&self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t1: f64, state1: &[f64]) -> DenseOutput {
+            let (h, k): (f64, &Vec<Vec<f64>>) = match &self.last_step {
+                Some(last) if last.t == t0 && last.state == state0 => (last.h, &last.stages),
+                _ => {
+                    let derivative0: Vec<f64> = system.derivative(t0, state0);
+                    let derivative1: Vec<f64> = system.derivative(t1, state1);
+                    return DenseOutput::hermite(t0, state0, &derivative0, t1, state1, &derivative1);
+                }
+            };
+
+            let n: usize = state0.len();
+            let r1: Vec<f64> = state0.to_vec();
+            let r2: Vec<f64> = (0..n).map(|i| state1[i] - state0[i]).collect();
+            let r3: Vec<f64> = (0..n).map(|i| h * k[0][i] - r2[i]).collect();
+            let r4: Vec<f64> = (0..n).map(|i| r2[i] - h * k[6][i] - r3[i]).collect();
+            let r5: Vec<f64> = (0..n).map(|i| h * (0..7).map(|j| D[j] * k[j][i]).sum::<f64>()).collect();
+
+            DenseOutput::new(t0, t1, move |t| {
+                if h == 0.0 {
+                    return r1.clone();
+                }
+                let theta: f64 = (t - t0) / h;
+                let theta1: f64 = 1.0 - theta;
+                (0..n)
+                    .map(|i| r1[i] + theta * (r2[i] + theta1 * (r3[i] + theta * (r4[i] + theta1 * r5[i]))))
+                    .collect()
+            })
+        }
+    }
+
+    /// Saves the tolerances together with the step size controller, the first same as last stage and
+    /// the last accepted step, so a restored solver takes exactly the steps the original would have
+    impl Checkpointable for DormandPrince {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("DormandPrince");
+            checkpoint.put("absolute_tolerance", self.absolute_tolerance);
+            checkpoint.put("relative_tolerance", self.relative_tolerance);
+            checkpoint.put("min_step", self.min_step);
+            checkpoint.put("max_step", self.max_step);
+            checkpoint.put_optional("initial_step", self.initial_step);
+            checkpoint.put_optional("step_size", self.step_size);
+            checkpoint.put("has_last_derivative", self.last_derivative.is_some());
+            if let Some((t, state, derivative)) = &self.last_derivative {
+                checkpoint.put("last_derivative.t", t);
+                checkpoint.put_list("last_derivative.state", state);
+                checkpoint.put_list("last_derivative.derivative", derivative);
+            }
+            checkpoint.put("has_last_step", self.last_step.is_some());
+            if let Some(last) = &self.last_step {
+                checkpoint.put("last_step.t", last.t);
+                checkpoint.put("last_step.h", last.h);
+                checkpoint.put_list("last_step.state", &last.state);
+                for (index, stage) in last.stages.iter().enumerate() {
+                    checkpoint.put_list(&format!("last_step.stage_{}", index), stage);
+                }
+            }
+            checkpoint.put_section("stats", &self.stats.to_checkpoint());
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<DormandPrince, CheckpointError> {
+            checkpoint.expect_kind("DormandPrince")?;
+            let last_derivative: Option<(f64, Vec<f64>, Vec<f64>)> = if checkpoint.get("has_last_derivative")? {
+                Some((
+                    checkpoint.get("last_derivative.t")?,
+                    checkpoint.get_list("last_derivative.state")?,
+                    checkpoint.get_list("last_derivative.derivative")?
+                ))
+            } else {
+                None
+            };
+            let last_step: Option<AcceptedStep> = if checkpoint.get("has_last_step")? {
+                Some(AcceptedStep {
+                    t: checkpoint.get("last_step.t")?,
+                    h: checkpoint.get("last_step.h")?,
+                    state: checkpoint.get_list("last_step.state")?,
+                    stages: (0..7)
+                        .map(|index| checkpoint.get_list(&format!("last_step.stage_{}", index)))
+                        .collect::<Result<Vec<Vec<f64>>, CheckpointError>>()?
+                })
+            } else {
+                None
+            };
+            Ok(DormandPrince {
+                absolute_tolerance: checkpoint.get("absolute_tolerance")?,
+                relative_tolerance: checkpoint.get("relative_tolerance")?,
+                min_step: checkpoint.get("min_step")?,
+                max_step: checkpoint.get("max_step")?,
+                initial_step: checkpoint.get_optional("initial_step")?,
+                step_size: checkpoint.get_optional("step_size")?,
+                last_derivative,
+                last_step,
+                stats: SolverStats::from_checkpoint(&checkpoint.section("stats"))?
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::dynamical_system::dynamical_system::test_systems::{ExponentialDecay, HarmonicOscillator};
+        use crate::integrators::integrators::Solution;
+
+        fn max_error(state: &[f64], exact: &[f64]) -> f64 {
+            state.iter().zip(exact.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max)
+        }
+
+        #[test]
+        fn exponential_decay_end_point_is_within_tolerance() {
+            let system: ExponentialDecay = ExponentialDecay { rate: 1.0 };
+            let solution: Solution = DormandPrince::new(1e-9, 1e-9).solve(&system, 0.0, &[1.0], 5.0).unwrap();
+            assert_eq!(*solution.times.last().unwrap(), 5.0);
+            assert!(max_error(solution.states.last().unwrap(), &system.solution(5.0)) < 1e-8);
+        }
+
+        #[test]
+        fn harmonic_oscillator_end_point_is_within_tolerance() {
+            // the error accumulated over three periods stays within a small multiple of the tolerance per step
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 2.0 };
+            for tolerance in [1e-6, 1e-8, 1e-10] {
+                let solution: Solution = DormandPrince::new(tolerance, tolerance).solve(&system, 0.0, &[1.0, 0.0], 10.0).unwrap();
+                let error: f64 = max_error(solution.states.last().unwrap(), &system.solution(10.0));
+                assert!(error < 20.0 * tolerance, "error {} at tolerance {}", error, tolerance);
+            }
+        }
+
+        #[test]
+        fn tighter_tolerance_rejects_steps() {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            let loose: SolverStats = DormandPrince::new(1e-2, 1e-2).with_initial_step(0.5).solve(&system, 0.0, &[1.0, 0.0], 1.0).unwrap().stats;
+            let tight: SolverStats = DormandPrince::new(1e-10, 1e-10).with_initial_step(0.5).solve(&system, 0.0, &[1.0, 0.0], 1.0).unwrap().stats;
+            assert_eq!(loose.rejected_steps, 0);
+            assert!(tight.rejected_steps > 0);
+            assert!(tight.accepted_steps > loose.accepted_steps);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+pub mod autodiff {
+    use std::ops::{Add, Div, Mul, Neg, Sub};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+
+    /// Number type a vector field can be written over, so that the same code evaluates it on
+    /// plain `f64` values and on `Dual` numbers for exact derivatives
+    pub trait Scalar:
+        Copy
+        + Add<Output = Self>
+        + Sub<Output = Self>
+        + Mul<Output = Self>
+        + Div<Output = Self>
+        + Neg<Output = Self>
+        + Add<f64, Output = Self>
+        + Sub<f64, Output = Self>
+        + Mul<f64, Output = Self>
+        + Div<f64, Output = Self>
+    {
+        /// Lifts a constant into the number type
+        fn constant(value: f64) -> Self;
+
+        /// Returns the real value, discarding any derivative part
+        fn value(&self) -> f64;
+
+        fn sin(self) -> Self;
+        fn cos(self) -> Self;
+        fn exp(self) -> Self;
+        fn ln(self) -> Self;
+        fn sqrt(self) -> Self;
+        fn powi(self, n: i32) -> Self;
+        fn powf(self, n: f64) -> Self;
+    }
+
+    impl Scalar for f64 {
+        fn constant(value: f64) -> f64 {
+            value
+        }
+
+        fn value(&self) -> f64 {
+            *self
+        }
+
+        fn sin(self) -> f64 {
+            f64::sin(self)
+        }
+
+        fn cos(self) -> f64 {
+            f64::cos(self)
+        }
+
+        fn exp(self) -> f64 {
+            f64::exp(self)
+        }
+
+        fn ln(self) -> f64 {
+            f64::ln(self)
+        }
+
+        fn sqrt(self) -> f64 {
+            f64::sqrt(self)
+        }
+
+        fn powi(self, n: i32) -> f64 {
+            f64::powi(self, n)
+        }
+
+        fn powf(self, n: f64) -> f64 {
+            f64::powf(self, n)
+        }
+    }
+
+    /// Dual number value + derivative * e with e^2 = 0, carrying a directional derivative through arithmetic
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct Dual {
+        pub value: f64,
+        pub derivative: f64
+    }
+
+    impl Dual {
+        /// Creates a Dual number
+        pub fn new(value: f64, derivative: f64) -> Dual {
+            Dual {
+                value,
+                derivative
+            }
+        }
+
+        /// Creates the Dual number of an independent variable, whose derivative with respect to itself is 1
+        pub fn variable(value: f64) -> Dual {
+            Dual::new(value, 1.0)
+        }
+    }
+
+    impl Add for Dual {
+        type Output = Dual;
+
+        fn add(self, other: Dual) -> Dual {
+            Dual::new(self.value + other.value, self.derivative + other.derivative)
+        }
+    }
+
+    impl Sub for Dual {
+        type Output = Dual;
+
+        fn sub(self, other: Dual) -> Dual {
+            Dual::new(self.value - other.value, self.derivative - other.derivative)
+        }
+    }
+
+    impl Mul for Dual {
+        type Output = Dual;
+
+        fn mul(self, other: Dual) -> Dual {
+            Dual::new(self.value * other.value, self.derivative * other.value + self.value * other.derivative)
+        }
+    }
+
+    impl Div for Dual {
+        type Output = Dual;
+
+        fn div(self, other: Dual) -> Dual {
+            Dual::new(
+                self.value / other.value,
+                (self.derivative * other.value - self.value * other.derivative) / (other.value * other.value)
+            )
+        }
+    }
+
+    impl Neg for Dual {
+        type Output = Dual;
+
+        fn neg(self) -> Dual {
+            Dual::new(-self.value, -self.derivative)
+        }
+    }
+
+    impl Add<f64> for Dual {
+        type Output = Dual;
+
+        fn add(self, other: f64) -> Dual {
+            Dual::new(self.value + other, self.derivative)
+        }
+    }
+
+    impl Sub<f64> for Dual {
+        type Output = Dual;
+
+        fn sub(self, other: f64) -> Dual {
+            Dual::new(self.value - other, self.derivative)
+        }
+    }
+
+    impl Mul<f64> for Dual {
+        type Output = Dual;
+
+        fn mul(self, other: f64) -> Dual {
+            Dual::new(self.value * other, self.derivative * other)
+        }
+    }
+
+    impl Div<f64> for Dual {
+        type Output = Dual;
+
+        fn div(self, other: f64) -> Dual {
+            Dual::new(self.value / other, self.derivative / other)
+        }
+    }
+
+    impl Scalar for Dual {
+        fn constant(value: f64) -> Dual {
+            Dual::new(value, 0.0)
+        }
+
+        fn value(&self) -> f64 {
+            self.value
+        }
+
+        fn sin(self) -> Dual {
+            Dual::new(self.value.sin(), self.derivative * self.value.cos())
+        }
+
+        fn cos(self) -> Dual {
+            Dual::new(self.value.cos(), -self.derivative * self.value.sin())
+        }
+
+        fn exp(self) -> Dual {
+            let exp: f64 = self.value.exp();
+            Dual::new(exp, self.derivative * exp)
+        }
+
+        fn ln(self) -> Dual {
+            Dual::new(self.value.ln(), self.derivative / self.value)
+        }
+
+        fn sqrt(self) -> Dual {
+            let sqrt: f64 = self.value.sqrt();
+            Dual::new(sqrt, self.derivative / (2.0 * sqrt))
+        }
+
+        /// The derivative is taken as exactly zero for n = 0 and for constants, where n x^(n - 1)
+        /// may be infinite at x = 0
+        fn powi(self, n: i32) -> Dual {
+            let derivative: f64 = if n == 0 || self.derivative == 0.0 { 0.0 } else { n as f64 * self.value.powi(n - 1) * self.derivative };
+            Dual::new(self.value.powi(n), derivative)
+        }
+
+        /// The derivative is taken as exactly zero for n = 0 and for constants, where n x^(n - 1)
+        /// may be infinite at x = 0
+        fn powf(self, n: f64) -> Dual {
+            let derivative: f64 = if n == 0.0 || self.derivative == 0.0 { 0.0 } else { n * self.value.powf(n - 1.0) * self.derivative };
+            Dual::new(self.value.powf(n), derivative)
+        }
+    }
+
+    /// A vector field written generically over the number type, which gets an exact Jacobian from `jacobian`
+    pub trait GenericVectorField {
+        /// Evaluates the vector field at time t
+        fn vector_field<T: Scalar>(&self, t: f64, state: &[T]) -> Vec<T>;
+    }
+
+    /// Returns the exact Jacobian of a generic vector field as rows, jacobian[i][j] = d f_i / d x_j,
+    /// using one forward mode pass per state variable
+    pub fn jacobian<F: GenericVectorField + ?Sized>(field: &F, t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+        let n: usize = state.len();
+        let mut jacobian: Vec<Vec<f64>> = vec![vec![0.0; n]; n];
+
+        for j in 0..n {
+            let seeded: Vec<Dual> = (0..n)
+                .map(|i| if i == j { Dual::variable(state[i]) } else { Dual::constant(state[i]) })
+                .collect();
+            for (row, value) in jacobian.iter_mut().zip(field.vector_field(t, &seeded)) {
+                row[j] = value.derivative;
+            }
+        }
+
+        jacobian
+    }
+
+    /// Wraps a system so that its Jacobian is computed by automatic differentiation of its generic vector field
+    #[derive(Debug, Clone)]
+    pub struct AutoDiff<S: DynamicalSystem + GenericVectorField>(pub S);
+
+    impl<S: DynamicalSystem + GenericVectorField> DynamicalSystem for AutoDiff<S> {
+        fn dimension(&self) -> usize {
+            self.0.dimension()
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            self.0.parameters()
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            self.0.variable_names()
+        }
+
+        fn initial_state(&self) -> Vec<f64> {
+            self.0.initial_state()
+        }
+
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+            self.0.derivative(t, state)
+        }
+
+        fn jacobian(&self, t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            jacobian(&self.0, t, state)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::double_pendulum::double_pendulum::DoublePendulum;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        fn assert_matrices_close(actual: &[Vec<f64>], expected: &[Vec<f64>], tolerance: f64) {
+            for (actual_row, expected_row) in actual.iter().zip(expected.iter()) {
+                for (a, b) in actual_row.iter().zip(expected_row.iter()) {
+                    assert!((a - b).abs() <= tolerance * b.abs().max(1.0), "{:?} against {:?}", actual, expected);
+                }
+            }
+        }
+
+        /// Analytic Jacobian of the double pendulum equations of motion at (theta_1, theta_2, omega_1, omega_2)
+        fn double_pendulum_jacobian(g: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            let (theta1, theta2, omega1, omega2): (f64, f64, f64, f64) = (state[0], state[1], state[2], state[3]);
+            let delta: f64 = theta1 - theta2;
+            let (sin_delta, cos_delta): (f64, f64) = delta.sin_cos();
+
+            // alpha_1 = numerator1 / d with numerator1 = -3 g sin(theta_1) - g sin(theta_1 - 2 theta_2) - 2 sin(delta) s,
+            // and alpha_2 = 2 sin(delta) u / d
+            let s: f64 = omega2.powi(2) + omega1.powi(2) * cos_delta;
+            let u: f64 = 2.0 * omega1.powi(2) + 2.0 * g * theta1.cos() + omega2.powi(2) * cos_delta;
+            let d: f64 = 3.0 - (2.0 * delta).cos();
+            let numerator1: f64 = -3.0 * g * theta1.sin() - g * (theta1 - 2.0 * theta2).sin() - 2.0 * sin_delta * s;
+            let numerator2: f64 = 2.0 * sin_delta * u;
+
+            let ds: [f64; 4] = [-omega1.powi(2) * sin_delta, omega1.powi(2) * sin_delta, 2.0 * omega1 * cos_delta, 2.0 * omega2];
+            let du: [f64; 4] = [-2.0 * g * theta1.sin() - omega2.powi(2) * sin_delta, omega2.powi(2) * sin_delta, 4.0 * omega1, 2.0 * omega2 * cos_delta];
+            let dd: [f64; 4] = [2.0 * (2.0 * delta).sin(), -2.0 * (2.0 * delta).sin(), 0.0, 0.0];
+            let dnumerator1: [f64; 4] = [
+                -3.0 * g * theta1.cos() - g * (theta1 - 2.0 * theta2).cos() - 2.0 * cos_delta * s - 2.0 * sin_delta * ds[0],
+                2.0 * g * (theta1 - 2.0 * theta2).cos() + 2.0 * cos_delta * s - 2.0 * sin_delta * ds[1],
+                -2.0 * sin_delta * ds[2],
+                -2.0 * sin_delta * ds[3]
+            ];
+            let dnumerator2: [f64; 4] = [
+                2.0 * cos_delta * u + 2.0 * sin_delta * du[0],
+                -2.0 * cos_delta * u + 2.0 * sin_delta * du[1],
+                2.0 * sin_delta * du[2],
+                2.0 * sin_delta * du[3]
+            ];
+
+            vec![
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+                (0..4).map(|k| (dnumerator1[k] * d - numerator1 * dd[k]) / d.powi(2)).collect(),
+                (0..4).map(|k| (dnumerator2[k] * d - numerator2 * dd[k]) / d.powi(2)).collect()
+            ]
+        }
+
+        #[test]
+        fn powers_have_finite_derivatives_at_zero() {
+            let x: Dual = Dual::variable(0.0);
+            assert_eq!(x.powi(0), Dual::new(1.0, 0.0));
+            assert_eq!(x.powi(1), Dual::new(0.0, 1.0));
+            assert_eq!(x.powi(2), Dual::new(0.0, 0.0));
+            assert_eq!(x.powf(2.0), Dual::new(0.0, 0.0));
+            assert_eq!(x.powf(0.0), Dual::new(1.0, 0.0));
+            assert_eq!(Dual::constant(0.0).powi(-1).derivative, 0.0);
+            assert_eq!(Dual::variable(3.0).powi(3), Dual::new(27.0, 27.0));
+        }
+
+        #[test]
+        fn lorenz_jacobian_matches_the_analytic_one() {
+            let lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 100);
+            for state in [[1.0, 2.0, 3.0], [0.0, 0.0, 0.0], [-8.5, 4.2, 27.0]] {
+                assert_matrices_close(&jacobian(&lorenz, 0.0, &state), &lorenz.jacobian(0.0, &state), 1e-14);
+            }
+        }
+
+        #[test]
+        fn double_pendulum_jacobian_matches_the_analytic_one() {
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 0.5, -0.3, 0.001, 9.81, 100);
+            for state in [[1.0, 2.0, 0.5, -0.3], [0.0, 0.0, 0.0, 0.0], [0.7, 0.7, 3.0, 0.0], [-2.0, 1.5, -4.0, 6.0]] {
+                assert_matrices_close(&jacobian(&pendulum, 0.0, &state), &double_pendulum_jacobian(9.81, &state), 1e-12);
+            }
+        }
+    }
+}
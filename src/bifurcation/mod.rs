@@ -0,0 +1,247 @@
+pub mod bifurcation {
+    use plotters::prelude::*;
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::ensemble::ensemble::parallel_map;
+    use crate::events::events::{Direction, Event, EventRecord};
+    use crate::integrators::integrators::{FixedStep, Integrator, OdeSolver};
+    use crate::poincare::poincare::PoincareSection;
+
+    /// What is recorded from the trajectory at each parameter value
+    #[derive(Clone)]
+    pub enum Observation {
+        /// Local maxima of the state variable with the given index, located where its derivative
+        /// crosses zero from above
+        Maxima(usize),
+        /// Value of the state variable with the given index at each crossing of a Poincare section
+        Section(PoincareSection, usize)
+    }
+
+    impl Observation {
+        /// Returns the index of the recorded state variable
+        pub fn variable(&self) -> usize {
+            match self {
+                Observation::Maxima(variable) => *variable,
+                Observation::Section(_, variable) => *variable
+            }
+        }
+    }
+
+    /// Values observed on the attractor at each value of a swept parameter
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct BifurcationDiagram {
+        pub parameter_name: String,
+        pub variable_name: String,
+        pub parameter_values: Vec<f64>,
+        /// observations[k] holds every value recorded at parameter_values[k]
+        pub observations: Vec<Vec<f64>>
+    }
+
+    impl BifurcationDiagram {
+        /// Returns every recorded point as a (parameter, value) pair
+        pub fn points(&self) -> Vec<(f64, f64)> {
+            self.parameter_values
+                .iter()
+                .zip(self.observations.iter())
+                .flat_map(|(&parameter, values)| values.iter().map(move |&value| (parameter, value)))
+                .collect()
+        }
+
+        /// Plots the bifurcation diagram, one pixel per recorded point
+        ///
+        /// *Params
+        ///
+        /// path: file the PNG is written to
+        ///
+        /// size: (width, height) of the image in pixels
+        pub fn display(&self, path: &str, size: (u32, u32)) {
+            let points: Vec<(f64, f64)> = self.points();
+            let (parameter_low, parameter_high): (f64, f64) = bounds(self.parameter_values.iter().cloned());
+            let (value_low, value_high): (f64, f64) = bounds(points.iter().map(|&(_, value)| value));
+
+            let root: DrawingArea<BitMapBackend<'_>, plotters::coord::Shift> = BitMapBackend::new(path, size).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+
+            let mut chart = ChartBuilder::on(&root)
+                .caption("Bifurcation Diagram", ("Arial", 20).into_font())
+                .margin(5)
+                .x_label_area_size(40)
+                .y_label_area_size(50)
+                .build_cartesian_2d(parameter_low..parameter_high, value_low..value_high)
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .x_desc(self.parameter_name.as_str())
+                .y_desc(self.variable_name.as_str())
+                .draw()
+                .unwrap();
+
+            chart
+                .draw_series(points.iter().map(|&point| Pixel::new(point, BLACK.mix(0.6))))
+                .unwrap();
+
+            root.present().unwrap();
+        }
+    }
+
+    /// Returns the range covered by a set of values, widened slightly so no point lies on the border
+    fn bounds<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+        let (low, high): (f64, f64) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)));
+        if !low.is_finite() || !high.is_finite() {
+            return (0.0, 1.0);
+        }
+        let margin: f64 = if high > low { 0.02 * (high - low) } else { 0.5 };
+        (low - margin, high + margin)
+    }
+
+    /// Sweep of a system parameter that records an observation of the attractor at each value, with
+    /// the parameter values integrated in parallel on all available CPU cores
+    pub struct BifurcationSweep<F> {
+        pub parameter_name: String,
+        pub parameter_values: Vec<f64>,
+        build: F,
+        pub observation: Observation,
+        pub integrator: Integrator,
+        pub dt: f64,
+        /// Time integrated at each parameter value before observations are recorded
+        pub transient: f64,
+        /// Time over which observations are recorded after the transient
+        pub duration: f64
+    }
+
+    impl<S, F> BifurcationSweep<F>
+    where
+        S: DynamicalSystem + Clone + 'static,
+        F: Fn(f64) -> S + Sync
+    {
+        /// Creates a BifurcationSweep over evenly spaced parameter values, recording the maxima of the
+        /// first state variable with RK4 steps of 0.01 over 500 time units after a transient of 500
+        ///
+        /// *Params
+        ///
+        /// parameter_name: name of the swept parameter, used to label the diagram
+        ///
+        /// start, end: first and last parameter values
+        ///
+        /// count: number of parameter values
+        ///
+        /// build: builds the system for a parameter value, including its initial state
+        pub fn new(parameter_name: &str, start: f64, end: f64, count: usize, build: F) -> BifurcationSweep<F> {
+            assert!(count >= 2, "at least two parameter values are needed");
+            BifurcationSweep {
+                parameter_name: parameter_name.to_string(),
+                parameter_values: (0..count).map(|i| start + (end - start) * i as f64 / (count - 1) as f64).collect(),
+                build,
+                observation: Observation::Maxima(0),
+                integrator: Integrator::RungeKutta4,
+                dt: 0.01,
+                transient: 500.0,
+                duration: 500.0
+            }
+        }
+
+        /// Replaces the parameter values
+        pub fn with_parameter_values(mut self, parameter_values: Vec<f64>) -> BifurcationSweep<F> {
+            assert!(!parameter_values.is_empty(), "at least one parameter value is needed");
+            self.parameter_values = parameter_values;
+            self
+        }
+
+        /// Sets what is recorded at each parameter value
+        pub fn with_observation(mut self, observation: Observation) -> BifurcationSweep<F> {
+            self.observation = observation;
+            self
+        }
+
+        /// Sets the integration scheme and step size
+        pub fn with_integrator(mut self, integrator: Integrator, dt: f64) -> BifurcationSweep<F> {
+            self.integrator = integrator;
+            self.dt = dt;
+            self
+        }
+
+        /// Sets the time integrated before observations are recorded
+        pub fn with_transient(mut self, transient: f64) -> BifurcationSweep<F> {
+            assert!(transient >= 0.0, "transient must not be negative");
+            self.transient = transient;
+            self
+        }
+
+        /// Sets the time over which observations are recorded
+        pub fn with_duration(mut self, duration: f64) -> BifurcationSweep<F> {
+            assert!(duration > 0.0, "duration must be positive");
+            self.duration = duration;
+            self
+        }
+
+        /// Returns the values observed at a single parameter value
+        pub fn observe(&self, parameter: f64) -> Vec<f64> {
+            let system: S = (self.build)(parameter);
+            let variable: usize = self.observation.variable();
+            assert!(variable < system.dimension(), "observed variable is outside the state space");
+            let mut solver: FixedStep = FixedStep::new(self.integrator, self.dt);
+            let t_end: f64 = self.transient + self.duration;
+
+            match &self.observation {
+                Observation::Maxima(_) => {
+                    let field: S = system.clone();
+                    let event: Event = Event::new(move |t, state| field.derivative(t, state)[variable]).with_direction(Direction::Falling);
+                    let records: Vec<EventRecord> = solver
+                        .locate_events(&system, 0.0, &system.initial_state(), t_end, &[event])
+                        .expect("fixed step integration cannot fail");
+                    records.into_iter().filter(|record| record.t >= self.transient).map(|record| record.state[variable]).collect()
+                }
+                Observation::Section(section, _) => {
+                    let crossings = section
+                        .compute(&mut solver, &system, 0.0, &system.initial_state(), t_end)
+                        .expect("fixed step integration cannot fail");
+                    crossings
+                        .times
+                        .iter()
+                        .zip(crossings.column(variable).iter())
+                        .filter(|(&t, _)| t >= self.transient)
+                        .map(|(_, &value)| value)
+                        .collect()
+                }
+            }
+        }
+
+        /// Runs the sweep and returns the diagram
+        pub fn run(&self) -> BifurcationDiagram {
+            let variable_name: String = (self.build)(self.parameter_values[0]).variable_names()[self.observation.variable()].clone();
+            BifurcationDiagram {
+                parameter_name: self.parameter_name.clone(),
+                variable_name,
+                parameter_values: self.parameter_values.clone(),
+                observations: parallel_map(&self.parameter_values, |&parameter| self.observe(parameter))
+            }
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::rossler::rossler::RosslerSystem;
+
+        /// Returns the number of distinct values, grouping values closer than the tolerance
+        fn branches(values: &[f64], tolerance: f64) -> usize {
+            let mut sorted: Vec<f64> = values.to_vec();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            1 + sorted.windows(2).filter(|w| w[1] - w[0] > tolerance).count()
+        }
+
+        #[test]
+        fn rossler_maxima_double_along_the_cascade() {
+            let sweep = BifurcationSweep::new("c", 2.5, 4.0, 2, |c| RosslerSystem::new(1.0, 1.0, 0.0, 0.2, 0.2, c, 0.01, 0))
+                .with_parameter_values(vec![2.5, 3.5, 4.0])
+                .with_transient(300.0)
+                .with_duration(200.0);
+            let diagram: BifurcationDiagram = sweep.run();
+
+            let counts: Vec<usize> = diagram.observations.iter().map(|values| branches(values, 1e-3)).collect();
+            assert_eq!(counts, vec![1, 2, 4], "{:?}", diagram.observations);
+            assert!(diagram.observations.iter().all(|values| values.len() > 20));
+        }
+    }
+}
@@ -1,10 +1,12 @@
 pub mod cellular_automata {
-    use rand::Rng;
+    use rand::{Rng, SeedableRng};
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::seeding::seeding::{self, ChaosRng};
     use std::thread;
     use std::time::Duration;
 
     #[derive(Clone)]
-    struct Cell {
+    pub struct Cell {
         pub alive: bool,
         pub dead: bool
     }
@@ -12,7 +14,9 @@ pub mod cellular_automata {
     pub struct Grid {
         pub height: usize,
         pub width: usize,
-        pub cells: Vec<Cell>
+        pub cells: Vec<Cell>,
+        /// Number of updates applied since the grid was created
+        pub generation: usize
     }
 
     impl Grid {
@@ -25,11 +29,23 @@ pub mod cellular_automata {
         /// width: x dimension of the grid
         /// 
         /// num_cells: number of cells in the grid
+        ///
+        /// The cells are drawn from `seeding::next_rng`, so they are reproducible under a fixed global seed
         pub fn new(height: usize, width: usize, num_cells: usize) -> Grid {
+            Grid::new_with_rng(height, width, num_cells, &mut seeding::next_rng())
+        }
+
+        /// Creates a new Grid object with cells drawn from a generator seeded with `seed`
+        pub fn new_seeded(height: usize, width: usize, num_cells: usize, seed: u64) -> Grid {
+            Grid::new_with_rng(height, width, num_cells, &mut ChaosRng::seed_from_u64(seed))
+        }
+
+        /// Creates a new Grid object with cells drawn from the given generator
+        pub fn new_with_rng<R: Rng + ?Sized>(height: usize, width: usize, num_cells: usize, rng: &mut R) -> Grid {
             let mut cells: Vec<Cell> = Vec::with_capacity(num_cells);
             for _ in 0..height * width {
                 cells.push(Cell {
-                    alive: rand::thread_rng().gen_bool(0.5), // 50% chance of being alive
+                    alive: rng.gen_bool(0.5), // 50% chance of being alive
                     dead: false,
                 });
             }
@@ -37,19 +53,26 @@ pub mod cellular_automata {
             Grid {
                 height,
                 width,
-                cells
+                cells,
+                generation: 0
             }
         }
 
         /// Simulates the Cellular Automata grid with an inputted number of iterations
         pub fn simulate(&mut self, steps: usize) {
             for _ in 0..steps {
-                self.update_cells();
+                self.step();
                 self.print_grid();
                 thread::sleep(Duration::from_millis(500));
             }
         }
 
+        /// Advances the grid by a single generation without printing it
+        pub fn step(&mut self) {
+            self.update_cells();
+            self.generation += 1;
+        }
+
         fn get_index(&self, x: usize, y: usize) -> usize {
             y * self.width + x
         }
@@ -117,4 +140,47 @@ pub mod cellular_automata {
             println!();
         }
     }
-}
\ No newline at end of file
+
+    impl Checkpointable for Grid {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("Grid");
+            checkpoint.put("height", self.height);
+            checkpoint.put("width", self.width);
+            checkpoint.put("generation", self.generation);
+            checkpoint.put("alive", self.cells.iter().map(|cell| if cell.alive { '1' } else { '0' }).collect::<String>());
+            checkpoint.put("dead", self.cells.iter().map(|cell| if cell.dead { '1' } else { '0' }).collect::<String>());
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Grid, CheckpointError> {
+            checkpoint.expect_kind("Grid")?;
+            let height: usize = checkpoint.get("height")?;
+            let width: usize = checkpoint.get("width")?;
+            let alive: Vec<bool> = parse_flags(checkpoint, "alive", height * width)?;
+            let dead: Vec<bool> = parse_flags(checkpoint, "dead", height * width)?;
+            Ok(Grid {
+                height,
+                width,
+                cells: alive.into_iter().zip(dead).map(|(alive, dead)| Cell { alive, dead }).collect(),
+                generation: checkpoint.get("generation")?
+            })
+        }
+    }
+
+    /// Reads a string of one '0' or '1' per cell
+    fn parse_flags(checkpoint: &Checkpoint, key: &str, count: usize) -> Result<Vec<bool>, CheckpointError> {
+        let value: &str = checkpoint.raw(key)?;
+        let invalid = || CheckpointError::Invalid { key: key.to_string(), value: value.to_string() };
+        if value.len() != count {
+            return Err(invalid());
+        }
+        value
+            .chars()
+            .map(|c| match c {
+                '1' => Ok(true),
+                '0' => Ok(false),
+                _ => Err(invalid())
+            })
+            .collect()
+    }
+}
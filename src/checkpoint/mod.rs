@@ -0,0 +1,233 @@
+pub mod checkpoint {
+    use std::error::Error;
+    use std::ffi::OsString;
+    use std::fmt;
+    use std::fmt::Display;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+
+    /// First line of every checkpoint file
+    const HEADER: &str = "# chaos_theory checkpoint v1";
+
+    /// Reasons a checkpoint can fail to be written or restored
+    #[derive(Debug)]
+    pub enum CheckpointError {
+        /// The checkpoint file could not be read or written
+        Io(io::Error),
+        /// The file does not start with the checkpoint header
+        UnknownFormat,
+        /// A line of the file is not of the form key=value
+        MalformedLine { line: usize },
+        /// A value needed to restore the state is missing
+        Missing { key: String },
+        /// A value could not be parsed into the expected type
+        Invalid { key: String, value: String },
+        /// The checkpoint was written by a different kind of object
+        WrongKind { expected: String, found: String }
+    }
+
+    impl fmt::Display for CheckpointError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                CheckpointError::Io(error) => write!(f, "checkpoint i/o failed: {}", error),
+                CheckpointError::UnknownFormat => write!(f, "not a checkpoint file"),
+                CheckpointError::MalformedLine { line } => write!(f, "line {} of the checkpoint is not of the form key=value", line),
+                CheckpointError::Missing { key } => write!(f, "checkpoint has no value for {}", key),
+                CheckpointError::Invalid { key, value } => write!(f, "checkpoint value {:?} for {} is invalid", value, key),
+                CheckpointError::WrongKind { expected, found } => write!(f, "expected a checkpoint of a {} but found one of a {}", expected, found)
+            }
+        }
+    }
+
+    impl Error for CheckpointError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                CheckpointError::Io(error) => Some(error),
+                _ => None
+            }
+        }
+    }
+
+    impl From<io::Error> for CheckpointError {
+        fn from(error: io::Error) -> CheckpointError {
+            CheckpointError::Io(error)
+        }
+    }
+
+    /// Ordered collection of key=value pairs describing the full state of a simulation.
+    ///
+    /// Floating point values are written with their shortest round trip representation, so a
+    /// restored state is bit for bit identical to the saved one. Nested objects are stored as
+    /// sections whose keys share a dotted prefix
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Checkpoint {
+        entries: Vec<(String, String)>
+    }
+
+    impl Checkpoint {
+        /// Creates a Checkpoint for an object of the given kind, checked again on restore
+        pub fn new(kind: &str) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::default();
+            checkpoint.put("kind", kind);
+            checkpoint
+        }
+
+        /// Returns an error unless the checkpoint was created for the given kind
+        pub fn expect_kind(&self, kind: &str) -> Result<(), CheckpointError> {
+            let found: String = self.get("kind")?;
+            if found != kind {
+                return Err(CheckpointError::WrongKind { expected: kind.to_string(), found });
+            }
+            Ok(())
+        }
+
+        /// Stores a value, replacing any previous value of the key
+        pub fn put<T: Display>(&mut self, key: &str, value: T) {
+            assert!(!key.contains('=') && !key.contains('\n'), "checkpoint keys cannot contain '=' or line breaks");
+            let value: String = value.to_string();
+            assert!(!value.contains('\n'), "checkpoint values cannot contain line breaks");
+            match self.entries.iter_mut().find(|(k, _)| k == key) {
+                Some(entry) => entry.1 = value,
+                None => self.entries.push((key.to_string(), value))
+            }
+        }
+
+        /// Stores an optional value, written as `none` when absent
+        pub fn put_optional<T: Display>(&mut self, key: &str, value: Option<T>) {
+            match value {
+                Some(value) => self.put(key, value),
+                None => self.put(key, "none")
+            }
+        }
+
+        /// Stores a list of values separated by commas
+        pub fn put_list<T: Display>(&mut self, key: &str, values: &[T]) {
+            let joined: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            self.put(key, joined.join(","));
+        }
+
+        /// Stores the entries of another checkpoint under the given prefix
+        pub fn put_section(&mut self, prefix: &str, section: &Checkpoint) {
+            for (key, value) in section.entries.iter() {
+                self.put(&format!("{}.{}", prefix, key), value);
+            }
+        }
+
+        /// Returns the raw text of a value
+        pub fn raw(&self, key: &str) -> Result<&str, CheckpointError> {
+            self.entries
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.as_str())
+                .ok_or_else(|| CheckpointError::Missing { key: key.to_string() })
+        }
+
+        /// Returns a value parsed into the requested type
+        pub fn get<T: FromStr>(&self, key: &str) -> Result<T, CheckpointError> {
+            parse(key, self.raw(key)?)
+        }
+
+        /// Returns a value stored with `put_optional`
+        pub fn get_optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, CheckpointError> {
+            match self.raw(key)? {
+                "none" => Ok(None),
+                value => parse(key, value).map(Some)
+            }
+        }
+
+        /// Returns a list stored with `put_list`
+        pub fn get_list<T: FromStr>(&self, key: &str) -> Result<Vec<T>, CheckpointError> {
+            let value: &str = self.raw(key)?;
+            if value.is_empty() {
+                return Ok(Vec::new());
+            }
+            value.split(',').map(|item| parse(key, item)).collect()
+        }
+
+        /// Returns the entries stored with `put_section` under the given prefix
+        pub fn section(&self, prefix: &str) -> Checkpoint {
+            let start: String = format!("{}.", prefix);
+            Checkpoint {
+                entries: self
+                    .entries
+                    .iter()
+                    .filter_map(|(key, value)| key.strip_prefix(&start).map(|key| (key.to_string(), value.clone())))
+                    .collect()
+            }
+        }
+
+        /// Writes the checkpoint to a file. The data is written to a temporary file first and then
+        /// moved into place, so a crash while saving leaves the previous checkpoint intact
+        pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+            let path: &Path = path.as_ref();
+            let mut temporary: OsString = path.as_os_str().to_owned();
+            temporary.push(".tmp");
+            let temporary: PathBuf = PathBuf::from(temporary);
+            fs::write(&temporary, self.to_string())?;
+            fs::rename(&temporary, path)?;
+            Ok(())
+        }
+
+        /// Reads a checkpoint written by `save`
+        pub fn load<P: AsRef<Path>>(path: P) -> Result<Checkpoint, CheckpointError> {
+            fs::read_to_string(path)?.parse()
+        }
+    }
+
+    impl fmt::Display for Checkpoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "{}", HEADER)?;
+            for (key, value) in self.entries.iter() {
+                writeln!(f, "{}={}", key, value)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl FromStr for Checkpoint {
+        type Err = CheckpointError;
+
+        fn from_str(text: &str) -> Result<Checkpoint, CheckpointError> {
+            let mut lines = text.lines();
+            if lines.next().map(str::trim_end) != Some(HEADER) {
+                return Err(CheckpointError::UnknownFormat);
+            }
+
+            let mut checkpoint: Checkpoint = Checkpoint::default();
+            for (index, line) in lines.enumerate() {
+                if line.trim().is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value): (&str, &str) = line.split_once('=').ok_or(CheckpointError::MalformedLine { line: index + 2 })?;
+                checkpoint.put(key, value);
+            }
+            Ok(checkpoint)
+        }
+    }
+
+    fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, CheckpointError> {
+        value.parse().map_err(|_| CheckpointError::Invalid { key: key.to_string(), value: value.to_string() })
+    }
+
+    /// Objects whose full state can be written to a checkpoint and restored from it, so that a
+    /// long simulation interrupted after a save continues exactly as if it had never stopped
+    pub trait Checkpointable: Sized {
+        /// Returns a checkpoint holding everything needed to restore the object
+        fn to_checkpoint(&self) -> Checkpoint;
+
+        /// Restores an object from a checkpoint returned by `to_checkpoint`
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Self, CheckpointError>;
+
+        /// Writes the state of the object to a checkpoint file
+        fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+            self.to_checkpoint().save(path)
+        }
+
+        /// Restores an object from a checkpoint file written by `save`
+        fn load<P: AsRef<Path>>(path: P) -> Result<Self, CheckpointError> {
+            Self::from_checkpoint(&Checkpoint::load(path)?)
+        }
+    }
+}
@@ -0,0 +1,279 @@
+pub mod delay {
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::sync::Arc;
+    use crate::trajectory::trajectory::{Sampling, Trajectory};
+
+    /// A system of delay differential equations dx/dt = f(t, x(t), x(t - tau)) with a constant lag tau
+    pub trait DelaySystem {
+        /// Returns the number of state variables of the system
+        fn dimension(&self) -> usize;
+
+        /// Returns the named parameters of the system
+        fn parameters(&self) -> Vec<(&'static str, f64)>;
+
+        /// Returns the name of each state variable. Defaults to x0, x1, ...
+        fn variable_names(&self) -> Vec<String> {
+            (0..self.dimension()).map(|i| format!("x{}", i)).collect()
+        }
+
+        /// Returns the constant lag tau
+        fn delay(&self) -> f64;
+
+        /// Evaluates the right hand side of the system
+        ///
+        /// *Params
+        ///
+        /// t: time at which the right hand side is evaluated
+        ///
+        /// state: state at time t
+        ///
+        /// delayed: state at time t - tau
+        fn derivative(&self, t: f64, state: &[f64], delayed: &[f64]) -> Vec<f64>;
+    }
+
+    impl<S: DelaySystem + ?Sized> DelaySystem for &S {
+        fn dimension(&self) -> usize {
+            (**self).dimension()
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            (**self).parameters()
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            (**self).variable_names()
+        }
+
+        fn delay(&self) -> f64 {
+            (**self).delay()
+        }
+
+        fn derivative(&self, t: f64, state: &[f64], delayed: &[f64]) -> Vec<f64> {
+            (**self).derivative(t, state, delayed)
+        }
+    }
+
+    /// State of a delay system before the start of the integration
+    #[derive(Clone)]
+    pub enum History {
+        /// The same state at every time before the start
+        Constant(Vec<f64>),
+        /// A state given as a function of time
+        Function(Arc<dyn Fn(f64) -> Vec<f64> + Send + Sync>)
+    }
+
+    impl History {
+        /// Creates a History from a function of time
+        pub fn function<F: Fn(f64) -> Vec<f64> + Send + Sync + 'static>(function: F) -> History {
+            History::Function(Arc::new(function))
+        }
+
+        /// Returns the state at time t
+        pub fn evaluate(&self, t: f64) -> Vec<f64> {
+            match self {
+                History::Constant(state) => state.clone(),
+                History::Function(function) => function(t)
+            }
+        }
+    }
+
+    impl fmt::Debug for History {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                History::Constant(state) => f.debug_tuple("Constant").field(state).finish(),
+                History::Function(_) => f.write_str("Function(..)")
+            }
+        }
+    }
+
+    /// A point of the computed solution kept for looking up delayed states
+    struct HistoryPoint {
+        t: f64,
+        state: Vec<f64>,
+        derivative: Vec<f64>
+    }
+
+    /// Fixed step fourth order Runge-Kutta integrator for delay systems. Delayed states inside the
+    /// computed solution are found by cubic Hermite interpolation of a buffer that spans one lag
+    pub struct DdeIntegrator<S: DelaySystem> {
+        system: S,
+        history: History,
+        dt: f64,
+        t: f64,
+        state: Vec<f64>,
+        buffer: VecDeque<HistoryPoint>,
+        steps_taken: usize
+    }
+
+    impl<S: DelaySystem> DdeIntegrator<S> {
+        /// Creates a DdeIntegrator starting at t = 0 from the history evaluated at 0
+        ///
+        /// *Params
+        ///
+        /// system: delay system to integrate
+        ///
+        /// history: state of the system for t <= 0
+        ///
+        /// dt: size of each step. Must not exceed the lag of the system
+        pub fn new(system: S, history: History, dt: f64) -> DdeIntegrator<S> {
+            assert!(dt <= system.delay(), "the step size must not exceed the delay");
+            let state: Vec<f64> = history.evaluate(0.0);
+            assert_eq!(state.len(), system.dimension(), "history does not match the system dimension");
+            DdeIntegrator {
+                system,
+                history,
+                dt,
+                t: 0.0,
+                state,
+                buffer: VecDeque::new(),
+                steps_taken: 0
+            }
+        }
+
+        /// Returns the current time
+        pub fn time(&self) -> f64 {
+            self.t
+        }
+
+        /// Returns the current state
+        pub fn state(&self) -> &[f64] {
+            &self.state
+        }
+
+        /// Returns the state at a time s no later than the current time
+        fn delayed_state(&self, s: f64) -> Vec<f64> {
+            if s <= 0.0 {
+                return self.history.evaluate(s);
+            }
+            if s >= self.t {
+                return self.state.clone();
+            }
+
+            // the buffer holds equally spaced points, with the current point last
+            let first: f64 = self.buffer[0].t;
+            let index: usize = (((s - first) / self.dt).floor().max(0.0) as usize).min(self.buffer.len() - 2);
+            let (p0, p1): (&HistoryPoint, &HistoryPoint) = (&self.buffer[index], &self.buffer[index + 1]);
+
+            let h: f64 = p1.t - p0.t;
+            let theta: f64 = (s - p0.t) / h;
+            let h00: f64 = (1.0 + 2.0 * theta) * (1.0 - theta).powi(2);
+            let h10: f64 = theta * (1.0 - theta).powi(2);
+            let h01: f64 = theta.powi(2) * (3.0 - 2.0 * theta);
+            let h11: f64 = theta.powi(2) * (theta - 1.0);
+            (0..p0.state.len())
+                .map(|i| h00 * p0.state[i] + h10 * h * p0.derivative[i] + h01 * p1.state[i] + h11 * h * p1.derivative[i])
+                .collect()
+        }
+
+        /// Advances the state by a single step
+        pub fn step(&mut self) {
+            let tau: f64 = self.system.delay();
+            let (t, dt): (f64, f64) = (self.t, self.dt);
+            let n: usize = self.state.len();
+
+            let k1: Vec<f64> = self.system.derivative(t, &self.state, &self.delayed_state(t - tau));
+            // the derivative at the current point completes the buffer up to the current time
+            self.buffer.push_back(HistoryPoint {
+                t,
+                state: self.state.clone(),
+                derivative: k1.clone()
+            });
+
+            let stage = |k: &[f64], scale: f64| -> Vec<f64> { (0..n).map(|i| self.state[i] + scale * k[i]).collect() };
+            let k2: Vec<f64> = self.system.derivative(t + 0.5 * dt, &stage(&k1, 0.5 * dt), &self.delayed_state(t + 0.5 * dt - tau));
+            let k3: Vec<f64> = self.system.derivative(t + 0.5 * dt, &stage(&k2, 0.5 * dt), &self.delayed_state(t + 0.5 * dt - tau));
+            let k4: Vec<f64> = self.system.derivative(t + dt, &stage(&k3, dt), &self.delayed_state(t + dt - tau));
+
+            for i in 0..n {
+                self.state[i] += dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+            }
+            self.steps_taken += 1;
+            self.t = self.steps_taken as f64 * dt;
+
+            while self.buffer.len() > 2 && self.buffer[1].t < self.t - tau - dt {
+                self.buffer.pop_front();
+            }
+        }
+
+        /// Takes the given number of steps and returns the points kept by the sampling
+        ///
+        /// *Params
+        ///
+        /// steps: number of steps
+        ///
+        /// sampling: transient and stride applied to the recorded points
+        pub fn simulate(&mut self, steps: usize, sampling: Sampling) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::new(
+                self.system.variable_names(),
+                self.system.parameters().into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+            );
+            let first_step: usize = self.steps_taken;
+
+            for i in 0..steps {
+                if sampling.keeps(first_step + i, self.dt) {
+                    trajectory.push(self.t, &self.state);
+                }
+                self.step();
+            }
+
+            trajectory
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// dx/dt = -x(t - 1)
+        struct NegativeFeedback;
+
+        impl DelaySystem for NegativeFeedback {
+            fn dimension(&self) -> usize {
+                1
+            }
+
+            fn parameters(&self) -> Vec<(&'static str, f64)> {
+                Vec::new()
+            }
+
+            fn delay(&self) -> f64 {
+                1.0
+            }
+
+            fn derivative(&self, _t: f64, _state: &[f64], delayed: &[f64]) -> Vec<f64> {
+                vec![-delayed[0]]
+            }
+        }
+
+        /// Method of steps solution of dx/dt = -x(t - 1) with x = 1 up to t = 0
+        fn exact(t: f64) -> f64 {
+            if t <= 1.0 { 1.0 - t } else { 1.0 - t + (t - 1.0).powi(2) / 2.0 }
+        }
+
+        #[test]
+        fn method_of_steps_solution_is_reproduced() {
+            // the solution is a polynomial of degree at most two on each step, which RK4 and the Hermite history integrate exactly
+            let mut integrator: DdeIntegrator<NegativeFeedback> = DdeIntegrator::new(NegativeFeedback, History::Constant(vec![1.0]), 0.1);
+            let trajectory: Trajectory = integrator.simulate(21, Sampling::default());
+            assert_eq!(trajectory.len(), 21);
+            for (t, x) in trajectory.times.iter().zip(trajectory.column(0).iter()) {
+                assert!((x - exact(*t)).abs() < 1e-13, "x({}) = {} against {}", t, x, exact(*t));
+            }
+        }
+
+        #[test]
+        fn delayed_states_interpolate_the_buffer() {
+            let mut integrator: DdeIntegrator<NegativeFeedback> = DdeIntegrator::new(NegativeFeedback, History::Constant(vec![1.0]), 0.1);
+            for _ in 0..17 {
+                integrator.step();
+            }
+            // the buffer spans one lag back from t = 1.7, and the solution is quadratic inside it after t = 1
+            for s in [0.75, 0.8, 1.0, 1.234, 1.65, 1.7] {
+                assert!((integrator.delayed_state(s)[0] - exact(s)).abs() < 1e-13, "{}", s);
+            }
+            assert_eq!(integrator.delayed_state(-0.5), vec![1.0]);
+        }
+    }
+}
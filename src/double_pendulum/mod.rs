@@ -1,6 +1,15 @@
 pub mod double_pendulum {
     use plotters::prelude::*;
-    use rand::Rng;
+    use rand::{Rng, SeedableRng};
+    use crate::autodiff::autodiff::{self, GenericVectorField, Scalar};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::SolverError;
+    use crate::seeding::seeding::{self, ChaosRng};
+    use crate::symplectic::symplectic::{HamiltonianSystem, SymplecticIntegrator};
+    use crate::trajectory::trajectory::{Sampling, Trajectory};
+
+    /// Double pendulum with unit masses and unit arm lengths
+    #[derive(Debug, Clone)]
     pub struct DoublePendulum {
         initial_theta_1: f64,
         initial_theta_2: f64,
@@ -8,7 +17,8 @@ pub mod double_pendulum {
         initial_omega_2: f64,
         dt: f64,
         g: f64,
-        steps: usize
+        steps: usize,
+        sampling: Sampling
     }
 
     impl DoublePendulum {
@@ -35,7 +45,8 @@ pub mod double_pendulum {
                 initial_omega_2,
                 dt,
                 g,
-                steps
+                steps,
+                sampling: Sampling::default()
             }
         }
 
@@ -50,8 +61,19 @@ pub mod double_pendulum {
         /// g: acceleration due to gravity
         /// 
         /// steps: number of iterations
+        ///
+        /// The attributes are drawn from `seeding::next_rng`, so they are reproducible under a fixed global seed
         pub fn new_random(dt: f64, g: f64, steps: usize) -> DoublePendulum {
-            let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
+            DoublePendulum::new_random_with_rng(dt, g, steps, &mut seeding::next_rng())
+        }
+
+        /// Generates a Double Pendulum model with random attributes drawn from a generator seeded with `seed`
+        pub fn new_random_seeded(dt: f64, g: f64, steps: usize, seed: u64) -> DoublePendulum {
+            DoublePendulum::new_random_with_rng(dt, g, steps, &mut ChaosRng::seed_from_u64(seed))
+        }
+
+        /// Generates a Double Pendulum model with random attributes drawn from the given generator
+        pub fn new_random_with_rng<R: Rng + ?Sized>(dt: f64, g: f64, steps: usize, rng: &mut R) -> DoublePendulum {
             let max_value: f64 = 90.0;
             let min_value: f64 = 0.0;
             let theta1: f64 = rng.gen_range(min_value..max_value);
@@ -72,51 +94,116 @@ pub mod double_pendulum {
                 initial_omega_2: omega2,
                 dt,
                 g,
-                steps
+                steps,
+                sampling: Sampling::default()
             }
         }
 
+        /// Discards the points of the simulation with times before the given duration. The discarded steps still count towards `steps`
+        pub fn with_transient(mut self, duration: f64) -> DoublePendulum {
+            self.sampling = self.sampling.with_transient(duration);
+            self
+        }
+
+        /// Keeps only every stride-th point of the simulation
+        pub fn with_stride(mut self, stride: usize) -> DoublePendulum {
+            self.sampling = self.sampling.with_stride(stride);
+            self
+        }
+
+        /// Returns the trajectory of the double pendulum in (theta_1, theta_2, omega_1, omega_2), integrated with semi-implicit Euler.
+        ///
+        /// This keeps the angular accelerations of the original implementation so its output is unchanged.
+        /// They are not those of `derivative`, which the solvers and analysis tools integrate, and they
+        /// do not conserve `energy`
+        pub fn simulate_trajectory(&mut self) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(self);
+            let mut state: Vec<f64> = self.initial_state();
+
+            for i in 0..self.steps {
+                let t: f64 = i as f64 * self.dt;
+                if self.sampling.keeps(i, self.dt) {
+                    trajectory.push(t, &state);
+                }
+
+                let (alpha1, alpha2): (f64, f64) = self.legacy_accelerations(&state);
+
+                state[2] += alpha1 * self.dt;
+                state[3] += alpha2 * self.dt;
+                state[0] += state[2] * self.dt;
+                state[1] += state[3] * self.dt;
+            }
+
+            trajectory
+        }
+
+        /// Returns the angular accelerations of the original `simulate` at a state (theta_1, theta_2, omega_1, omega_2)
+        fn legacy_accelerations(&self, state: &[f64]) -> (f64, f64) {
+            let (theta1, theta2, omega1, omega2): (f64, f64, f64, f64) = (state[0], state[1], state[2], state[3]);
+
+            let numerator_alpha1: f64 = -self.g * (2.0 * theta1.sin()
+            + (theta1 - 2.0 * theta2).sin()
+            + 2.0 * (theta1 - theta2).sin() * (omega2.powi(2) + omega1.powi(2) * (theta1 - theta2).cos()));
+            let denominator_alpha1: f64 = 3.0 - (2.0 * theta1 - 2.0 * theta2).cos();
+            let alpha1: f64 = numerator_alpha1 / denominator_alpha1;
+            let term1_alpha2: f64 = 2.0 * (theta1 - theta2).sin();
+            let term2_alpha2: f64 = 2.0 * theta1.cos() * omega1.powi(2);
+            let term3_alpha2: f64 =
+                self.g * (2.0 * theta1.sin() + (theta1 - 2.0 * theta2).sin() + 2.0 * (theta1 - theta2).sin()
+                    * (omega2.powi(2) + omega1.powi(2) * (theta1 - theta2).cos()));
+            let numerator_alpha2: f64 = term1_alpha2 * (term2_alpha2 + term3_alpha2);
+            let denominator_alpha2: f64 = 3.0 - (2.0 * theta1 - 2.0 * theta2).cos();
+            let alpha2: f64 = numerator_alpha2 / denominator_alpha2;
+
+            (alpha1, alpha2)
+        }
+
         /// Returns the trajectories of the double pendulum
         pub fn simulate(&mut self) -> (Vec<f64>, Vec<f64>) {
-            let mut theta1: f64 = self.initial_theta_1;
-            let mut theta2: f64 = self.initial_theta_2;
-            let mut omega1: f64 = self.initial_omega_1;
-            let mut omega2: f64 = self.initial_omega_2;
+            self.simulate_trajectory().into()
+        }
+
+        /// Returns the trajectories of the double pendulum integrated with a symplectic scheme,
+        /// together with the total energy at every step. Fails when an implicit stage does not
+        /// converge, which happens when dt is too large for the angular velocities reached
+        ///
+        /// *Params
+        ///
+        /// integrator: symplectic scheme used for each step
+        #[allow(clippy::type_complexity)]
+        pub fn simulate_symplectic(&self, integrator: SymplecticIntegrator) -> Result<((Vec<f64>, Vec<f64>), Vec<f64>), SolverError> {
+            let state: Vec<f64> = self.initial_state();
+            let mut q: Vec<f64> = vec![state[0], state[1]];
+            let mut p: Vec<f64> = self.momenta(&state);
 
             let mut theta1_values: Vec<f64> = Vec::with_capacity(self.steps);
             let mut theta2_values: Vec<f64> = Vec::with_capacity(self.steps);
+            let mut energies: Vec<f64> = Vec::with_capacity(self.steps);
+
+            for i in 0..self.steps {
+                if self.sampling.keeps(i, self.dt) {
+                    theta1_values.push(q[0]);
+                    theta2_values.push(q[1]);
+                    energies.push(self.hamiltonian(&q, &p));
+                }
 
-            for _ in 0..self.steps {
-                theta1_values.push(theta1);
-                theta2_values.push(theta2);
-
-                let numerator_alpha1: f64 = -self.g * (2.0 * theta1.sin()
-                + (theta1 - 2.0 * theta2).sin()
-                + 2.0 * (theta1 - theta2).sin() * (omega2.powi(2) + omega1.powi(2) * (theta1 - theta2).cos()));
-                let denominator_alpha1 = 3.0 - (2.0 * theta1 - 2.0 * theta2).cos();
-                let alpha1: f64 = numerator_alpha1 / denominator_alpha1;
-                let term1_alpha2: f64 = 2.0 * (theta1 - theta2).sin();
-                let term2_alpha2: f64 = 2.0 * theta1.cos() * omega1.powi(2);
-                let term3_alpha2: f64 =
-                    self.g * (2.0 * theta1.sin() + (theta1 - 2.0 * theta2).sin() + 2.0 * (theta1 - theta2).sin()
-                        * (omega2.powi(2) + omega1.powi(2) * (theta1 - theta2).cos()));
-                let numerator_alpha2: f64 = term1_alpha2 * (term2_alpha2 + term3_alpha2);
-                let denominator_alpha2: f64 = 3.0 - (2.0 * theta1 - 2.0 * theta2).cos();
-                let alpha2: f64 = numerator_alpha2 / denominator_alpha2;
-
-                omega1 = omega1 + alpha1 * self.dt;
-                omega2 = omega2 + alpha2 * self.dt;
-                theta1 = theta1 + omega1 * self.dt;
-                theta2 = theta2 + omega2 * self.dt;
-
-                //println!("Theta 1: {}", theta1);
-                //println!("Theta 2: {}", theta2);
-                //println!("Omega 1: {}", omega1);
-                //println!("Omega 2: {}", omega2);
+                (q, p) = integrator
+                    .step(self, &q, &p, self.dt)
+                    .ok_or(SolverError::ImplicitStageDidNotConverge { t: i as f64 * self.dt, step: self.dt })?;
             }
-            //println!("Theta 1 Values: {:?}", theta1_values);
-            //println!("Theta 2 Values: {:?}", theta2_values);
-            (theta1_values, theta2_values)
+
+            Ok(((theta1_values, theta2_values), energies))
+        }
+
+        /// Returns the total (kinetic plus potential) energy of a state (theta_1, theta_2, omega_1, omega_2)
+        pub fn energy(&self, state: &[f64]) -> f64 {
+            self.hamiltonian(&state[..2], &self.momenta(state))
+        }
+
+        /// Returns the canonical momenta (p_1, p_2) conjugate to the angles of a state (theta_1, theta_2, omega_1, omega_2)
+        pub fn momenta(&self, state: &[f64]) -> Vec<f64> {
+            let cos_delta: f64 = (state[0] - state[1]).cos();
+            vec![2.0 * state[2] + state[3] * cos_delta, state[3] + state[2] * cos_delta]
         }
 
         /// Plots the Double Pendulum trajectories
@@ -129,7 +216,7 @@ pub mod double_pendulum {
                 .margin(5)
                 .x_label_area_size(40)
                 .y_label_area_size(40)
-                .build_cartesian_2d(0.0..self.steps as f64, -100.0..100.0)
+                .build_cartesian_2d(self.sampling.transient_steps(self.dt) as f64..self.steps as f64, -100.0..100.0)
                 .unwrap();
 
             chart
@@ -140,16 +227,178 @@ pub mod double_pendulum {
                 .unwrap();
 
             chart
-                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (i as f64, val)), &RED))
+                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &RED))
                 .unwrap()
                 .label("Pendulum 1")
-                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
             chart
-                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (i as f64, val)), &GREEN))
+                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &GREEN))
                 .unwrap()
                 .label("Pendulum 2")
-                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], &GREEN));
+                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], GREEN));
+        }
+    }
+
+    impl DynamicalSystem for DoublePendulum {
+        fn dimension(&self) -> usize {
+            4
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            vec![("g", self.g)]
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            vec!["theta_1".to_string(), "theta_2".to_string(), "omega_1".to_string(), "omega_2".to_string()]
+        }
+
+        /// The state is ordered as (theta_1, theta_2, omega_1, omega_2)
+        fn initial_state(&self) -> Vec<f64> {
+            vec![self.initial_theta_1, self.initial_theta_2, self.initial_omega_1, self.initial_omega_2]
+        }
+
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+            self.vector_field(t, state)
+        }
+
+        fn jacobian(&self, t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            autodiff::jacobian(self, t, state)
+        }
+    }
+
+    impl GenericVectorField for DoublePendulum {
+        /// Equations of motion of the unit mass, unit length double pendulum, the Euler-Lagrange form of
+        /// Hamilton's equations for `HamiltonianSystem::hamiltonian`, so that `energy` is conserved
+        fn vector_field<T: Scalar>(&self, _t: f64, state: &[T]) -> Vec<T> {
+            let (theta1, theta2, omega1, omega2): (T, T, T, T) = (state[0], state[1], state[2], state[3]);
+
+            let delta: T = theta1 - theta2;
+            let denominator: T = T::constant(3.0) - (delta * 2.0).cos();
+            let numerator_alpha1: T = -theta1.sin() * (3.0 * self.g)
+                - (theta1 - theta2 * 2.0).sin() * self.g
+                - delta.sin() * (omega2.powi(2) + omega1.powi(2) * delta.cos()) * 2.0;
+            let alpha1: T = numerator_alpha1 / denominator;
+            let numerator_alpha2: T = delta.sin() * 2.0
+                * (omega1.powi(2) * 2.0 + theta1.cos() * (2.0 * self.g) + omega2.powi(2) * delta.cos());
+            let alpha2: T = numerator_alpha2 / denominator;
+
+            vec![omega1, omega2, alpha1, alpha2]
+        }
+    }
+
+    impl HamiltonianSystem for DoublePendulum {
+        fn degrees_of_freedom(&self) -> usize {
+            2
+        }
+
+        fn hamiltonian(&self, q: &[f64], p: &[f64]) -> f64 {
+            let delta: f64 = q[0] - q[1];
+            let kinetic: f64 = (p[0].powi(2) - 2.0 * delta.cos() * p[0] * p[1] + 2.0 * p[1].powi(2))
+                / (2.0 * (1.0 + delta.sin().powi(2)));
+            let potential: f64 = -2.0 * self.g * q[0].cos() - self.g * q[1].cos();
+            kinetic + potential
+        }
+
+        fn dh_dq(&self, q: &[f64], p: &[f64]) -> Vec<f64> {
+            let delta: f64 = q[0] - q[1];
+            let (sin_delta, cos_delta): (f64, f64) = delta.sin_cos();
+            let denominator: f64 = 1.0 + sin_delta.powi(2);
+            let numerator: f64 = p[0].powi(2) - 2.0 * cos_delta * p[0] * p[1] + 2.0 * p[1].powi(2);
+            let dkinetic_ddelta: f64 = sin_delta * p[0] * p[1] / denominator
+                - numerator * sin_delta * cos_delta / denominator.powi(2);
+            vec![
+                2.0 * self.g * q[0].sin() + dkinetic_ddelta,
+                self.g * q[1].sin() - dkinetic_ddelta
+            ]
+        }
+
+        fn dh_dp(&self, q: &[f64], p: &[f64]) -> Vec<f64> {
+            let delta: f64 = q[0] - q[1];
+            let denominator: f64 = 1.0 + delta.sin().powi(2);
+            vec![
+                (p[0] - delta.cos() * p[1]) / denominator,
+                (2.0 * p[1] - delta.cos() * p[0]) / denominator
+            ]
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::integrators::integrators::Integrator;
+        use crate::symplectic::symplectic::relative_energy_drift;
+
+        fn max_drift(energies: &[f64]) -> f64 {
+            relative_energy_drift(energies).iter().fold(0.0, |max: f64, drift| max.max(drift.abs()))
+        }
+
+        #[test]
+        fn symplectic_schemes_bound_the_energy_drift() {
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 50.0, 0.0, 0.001, 9.81, 20000);
+            for (integrator, tolerance) in [
+                (SymplecticIntegrator::StormerVerlet, 2e-3),
+                (SymplecticIntegrator::Yoshida4, 1e-4),
+                (SymplecticIntegrator::ImplicitMidpoint, 1e-3)
+            ] {
+                let (_, energies) = pendulum.simulate_symplectic(integrator).unwrap();
+                assert!(max_drift(&energies) < tolerance, "{:?} drifted by {}", integrator, max_drift(&energies));
+            }
+        }
+
+        /// Returns the largest change of `energy` along an RK4 integration of `derivative` up to t = 5
+        fn rk4_energy_error(pendulum: &DoublePendulum, dt: f64) -> f64 {
+            let mut state: Vec<f64> = pendulum.initial_state();
+            let initial_energy: f64 = pendulum.energy(&state);
+            let mut error: f64 = 0.0;
+            for i in 0..(5.0 / dt).round() as usize {
+                state = Integrator::RungeKutta4.step(pendulum, i as f64 * dt, &state, dt);
+                error = error.max((pendulum.energy(&state) - initial_energy).abs());
+            }
+            error
+        }
+
+        #[test]
+        fn rk4_on_the_vector_field_conserves_the_energy_to_fourth_order() {
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 0.0, 0.0, 0.001, 9.81, 0);
+            let coarse: f64 = rk4_energy_error(&pendulum, 2e-3);
+            let fine: f64 = rk4_energy_error(&pendulum, 1e-3);
+            assert!(coarse < 1e-6, "{}", coarse);
+            assert!(coarse / fine > 12.0 && coarse / fine < 20.0, "{} against {}", coarse, fine);
+        }
+
+        #[test]
+        fn vector_field_follows_hamiltons_equations() {
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 0.0, 0.0, 0.001, 9.81, 0);
+            for state in [[1.0, 2.0, 0.5, -0.3], [-2.0, 1.5, -4.0, 6.0]] {
+                let derivative: Vec<f64> = pendulum.derivative(0.0, &state);
+                let p: Vec<f64> = pendulum.momenta(&state);
+                let dh_dp: Vec<f64> = pendulum.dh_dp(&state[..2], &p);
+                let dh_dq: Vec<f64> = pendulum.dh_dq(&state[..2], &p);
+
+                // dp/dt along the flow, by the chain rule through momenta(theta, omega)
+                let (sin_delta, cos_delta): (f64, f64) = (state[0] - state[1]).sin_cos();
+                let delta_rate: f64 = derivative[0] - derivative[1];
+                let p_rate: [f64; 2] = [
+                    2.0 * derivative[2] + derivative[3] * cos_delta - state[3] * sin_delta * delta_rate,
+                    derivative[3] + derivative[2] * cos_delta - state[2] * sin_delta * delta_rate
+                ];
+                for k in 0..2 {
+                    assert!((derivative[k] - dh_dp[k]).abs() < 1e-12);
+                    assert!((p_rate[k] + dh_dq[k]).abs() < 1e-10, "{:?} against {:?}", p_rate, dh_dq);
+                }
+            }
+        }
+
+        #[test]
+        fn unconverged_implicit_stages_are_reported() {
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 50.0, 0.0, 0.01, 9.81, 10000);
+            for integrator in [SymplecticIntegrator::Yoshida4, SymplecticIntegrator::ImplicitMidpoint] {
+                match pendulum.simulate_symplectic(integrator) {
+                    Err(SolverError::ImplicitStageDidNotConverge { step, .. }) => assert_eq!(step, 0.01),
+                    other => panic!("{:?} returned {:?}", integrator, other.map(|(_, energies)| max_drift(&energies)))
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,178 @@
+pub mod dynamical_system {
+    /// A continuous time dynamical system described by a vector field dx/dt = f(t, x).
+    ///
+    /// Implementing this trait is all that is needed for a system to be used with the
+    /// integrators and analysis tools in this crate, including systems defined outside of it.
+    pub trait DynamicalSystem {
+        /// Returns the number of state variables of the system
+        fn dimension(&self) -> usize;
+
+        /// Returns the named parameters of the system
+        fn parameters(&self) -> Vec<(&'static str, f64)>;
+
+        /// Returns the name of each state variable. Defaults to x0, x1, ...
+        fn variable_names(&self) -> Vec<String> {
+            (0..self.dimension()).map(|i| format!("x{}", i)).collect()
+        }
+
+        /// Returns the state the system starts from
+        fn initial_state(&self) -> Vec<f64>;
+
+        /// Evaluates the vector field of the system
+        ///
+        /// *Params
+        ///
+        /// t: time at which the vector field is evaluated
+        ///
+        /// state: point in state space, of length `dimension()`
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64>;
+
+        /// Returns the Jacobian matrix of the vector field as rows, jacobian[i][j] = d f_i / d x_j
+        ///
+        /// The default implementation uses forward finite differences. Systems with a known
+        /// analytic Jacobian should override it
+        fn jacobian(&self, t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            let n: usize = state.len();
+            let derivative: Vec<f64> = self.derivative(t, state);
+            let mut jacobian: Vec<Vec<f64>> = vec![vec![0.0; n]; n];
+
+            for j in 0..n {
+                let step: f64 = f64::EPSILON.sqrt() * state[j].abs().max(1.0);
+                let mut perturbed: Vec<f64> = state.to_vec();
+                perturbed[j] += step;
+                let perturbed_derivative: Vec<f64> = self.derivative(t, &perturbed);
+                for i in 0..n {
+                    jacobian[i][j] = (perturbed_derivative[i] - derivative[i]) / step;
+                }
+            }
+
+            jacobian
+        }
+    }
+
+    impl<S: DynamicalSystem + ?Sized> DynamicalSystem for &S {
+        fn dimension(&self) -> usize {
+            (**self).dimension()
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            (**self).parameters()
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            (**self).variable_names()
+        }
+
+        fn initial_state(&self) -> Vec<f64> {
+            (**self).initial_state()
+        }
+
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+            (**self).derivative(t, state)
+        }
+
+        fn jacobian(&self, t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            (**self).jacobian(t, state)
+        }
+    }
+
+    /// Systems with known solutions, shared by the tests of the solvers and analysis tools
+    #[cfg(test)]
+    pub mod test_systems {
+        use super::DynamicalSystem;
+
+        /// dx/dt = -rate x, solved by x(t) = x(0) e^(-rate t)
+        pub struct ExponentialDecay {
+            pub rate: f64
+        }
+
+        impl ExponentialDecay {
+            /// Returns the exact solution at time t starting from x(0) = 1
+            pub fn solution(&self, t: f64) -> Vec<f64> {
+                vec![(-self.rate * t).exp()]
+            }
+        }
+
+        impl DynamicalSystem for ExponentialDecay {
+            fn dimension(&self) -> usize {
+                1
+            }
+
+            fn parameters(&self) -> Vec<(&'static str, f64)> {
+                vec![("rate", self.rate)]
+            }
+
+            fn initial_state(&self) -> Vec<f64> {
+                vec![1.0]
+            }
+
+            fn derivative(&self, _t: f64, state: &[f64]) -> Vec<f64> {
+                vec![-self.rate * state[0]]
+            }
+        }
+
+        /// Harmonic oscillator dx/dt = omega y, dy/dt = -omega x, solved by (cos omega t, -sin omega t)
+        pub struct HarmonicOscillator {
+            pub omega: f64
+        }
+
+        impl HarmonicOscillator {
+            /// Returns the exact solution at time t starting from (1, 0)
+            pub fn solution(&self, t: f64) -> Vec<f64> {
+                vec![(self.omega * t).cos(), -(self.omega * t).sin()]
+            }
+        }
+
+        impl DynamicalSystem for HarmonicOscillator {
+            fn dimension(&self) -> usize {
+                2
+            }
+
+            fn parameters(&self) -> Vec<(&'static str, f64)> {
+                vec![("omega", self.omega)]
+            }
+
+            fn initial_state(&self) -> Vec<f64> {
+                vec![1.0, 0.0]
+            }
+
+            fn derivative(&self, _t: f64, state: &[f64]) -> Vec<f64> {
+                vec![self.omega * state[1], -self.omega * state[0]]
+            }
+
+            fn jacobian(&self, _t: f64, _state: &[f64]) -> Vec<Vec<f64>> {
+                vec![vec![0.0, self.omega], vec![-self.omega, 0.0]]
+            }
+        }
+
+        /// Van der Pol oscillator dx/dt = y, dy/dt = mu (1 - x^2) y - x, which is stiff for large mu
+        pub struct VanDerPol {
+            pub mu: f64
+        }
+
+        impl DynamicalSystem for VanDerPol {
+            fn dimension(&self) -> usize {
+                2
+            }
+
+            fn parameters(&self) -> Vec<(&'static str, f64)> {
+                vec![("mu", self.mu)]
+            }
+
+            fn initial_state(&self) -> Vec<f64> {
+                vec![2.0, 0.0]
+            }
+
+            fn derivative(&self, _t: f64, state: &[f64]) -> Vec<f64> {
+                vec![state[1], self.mu * (1.0 - state[0] * state[0]) * state[1] - state[0]]
+            }
+
+            fn jacobian(&self, _t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+                vec![
+                    vec![0.0, 1.0],
+                    vec![-2.0 * self.mu * state[0] * state[1] - 1.0, self.mu * (1.0 - state[0] * state[0])]
+                ]
+            }
+        }
+    }
+}
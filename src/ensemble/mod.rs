@@ -0,0 +1,250 @@
+pub mod ensemble {
+    use std::thread;
+    use rand::{Rng, SeedableRng};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::Integrator;
+    use crate::seeding::seeding::ChaosRng;
+    use crate::trajectory::trajectory::{Sampling, Trajectory, TrajectoryStream};
+
+    /// Applies a function to every item on all available CPU cores and returns the results in item order
+    pub fn parallel_map<T: Sync, R: Send, F: Fn(&T) -> R + Sync>(items: &[T], function: F) -> Vec<R> {
+        if items.is_empty() {
+            return Vec::new();
+        }
+        let threads: usize = thread::available_parallelism().map_or(1, |n| n.get()).min(items.len());
+        let chunk_size: usize = items.len().div_ceil(threads);
+        let function: &F = &function;
+
+        thread::scope(|scope| {
+            let handles: Vec<thread::ScopedJoinHandle<'_, Vec<R>>> = items
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(function).collect()))
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+
+    /// Mean and variance of every state variable across the members of an ensemble, at each recorded time
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct EnsembleStatistics {
+        pub times: Vec<f64>,
+        /// mean[k][i] is the mean of state variable i at times[k]
+        pub mean: Vec<Vec<f64>>,
+        /// variance[k][i] is the population variance of state variable i at times[k]
+        pub variance: Vec<Vec<f64>>
+    }
+
+    /// Running mean and sum of squared deviations over a share of the members of an ensemble
+    #[derive(Default)]
+    struct RunningMoments {
+        count: f64,
+        times: Vec<f64>,
+        mean: Vec<Vec<f64>>,
+        squared_deviations: Vec<Vec<f64>>
+    }
+
+    /// Batch of systems, each paired with the initial condition it is integrated from
+    #[derive(Debug, Clone)]
+    pub struct Ensemble<S: DynamicalSystem + Sync> {
+        pub members: Vec<(S, Vec<f64>)>
+    }
+
+    impl<S: DynamicalSystem + Sync> Ensemble<S> {
+        /// Creates an Ensemble of one system integrated from several initial conditions
+        pub fn from_initial_conditions(system: S, initial_conditions: Vec<Vec<f64>>) -> Ensemble<S>
+        where
+            S: Clone
+        {
+            Ensemble {
+                members: initial_conditions.into_iter().map(|state| (system.clone(), state)).collect()
+            }
+        }
+
+        /// Creates an Ensemble of systems with different parameters, each integrated from its own initial state
+        pub fn from_systems(systems: Vec<S>) -> Ensemble<S> {
+            Ensemble {
+                members: systems
+                    .into_iter()
+                    .map(|system| {
+                        let state: Vec<f64> = system.initial_state();
+                        (system, state)
+                    })
+                    .collect()
+            }
+        }
+
+        /// Creates an Ensemble of initial conditions drawn uniformly from a cube around the initial state of a system
+        ///
+        /// *Params
+        ///
+        /// system: system whose initial state is perturbed
+        ///
+        /// count: number of members
+        ///
+        /// radius: half width of the cube of perturbations
+        ///
+        /// seed: seed of the perturbations
+        pub fn perturbed(system: S, count: usize, radius: f64, seed: u64) -> Ensemble<S>
+        where
+            S: Clone
+        {
+            let mut rng: ChaosRng = ChaosRng::seed_from_u64(seed);
+            let center: Vec<f64> = system.initial_state();
+            let initial_conditions: Vec<Vec<f64>> = (0..count)
+                .map(|_| center.iter().map(|x| x + rng.gen_range(-radius..=radius)).collect())
+                .collect();
+            Ensemble::from_initial_conditions(system, initial_conditions)
+        }
+
+        /// Returns the number of members
+        pub fn len(&self) -> usize {
+            self.members.len()
+        }
+
+        /// Returns whether the ensemble has no members
+        pub fn is_empty(&self) -> bool {
+            self.members.is_empty()
+        }
+
+        /// Applies a function to every (system, initial condition) member in parallel
+        pub fn map<R: Send, F: Fn(&S, &[f64]) -> R + Sync>(&self, function: F) -> Vec<R> {
+            parallel_map(&self.members, |(system, state)| function(system, state))
+        }
+
+        /// Integrates every member in parallel and returns their trajectories in member order
+        ///
+        /// *Params
+        ///
+        /// integrator: scheme used for each step
+        ///
+        /// dt: size of each step
+        ///
+        /// steps: number of steps, including the transient
+        ///
+        /// sampling: transient and stride applied to the recorded points
+        pub fn trajectories(&self, integrator: Integrator, dt: f64, steps: usize, sampling: Sampling) -> Vec<Trajectory> {
+            self.map(|system, state| {
+                TrajectoryStream::new(system, integrator, dt)
+                    .with_initial_state(state.to_vec())
+                    .with_steps(steps)
+                    .with_sampling(sampling)
+                    .collect_trajectory()
+            })
+        }
+
+        /// Integrates every member in parallel and reduces them to the ensemble mean and variance,
+        /// without keeping the member trajectories
+        ///
+        /// *Params
+        ///
+        /// integrator: scheme used for each step
+        ///
+        /// dt: size of each step
+        ///
+        /// steps: number of steps, including the transient
+        ///
+        /// sampling: transient and stride applied to the recorded points
+        pub fn statistics(&self, integrator: Integrator, dt: f64, steps: usize, sampling: Sampling) -> EnsembleStatistics {
+            let threads: usize = thread::available_parallelism().map_or(1, |n| n.get());
+            self.statistics_in_shares(integrator, dt, steps, sampling, threads)
+        }
+
+        /// Computes the ensemble statistics with the members split into the given number of shares,
+        /// whose running moments are accumulated in parallel and then merged
+        fn statistics_in_shares(&self, integrator: Integrator, dt: f64, steps: usize, sampling: Sampling, shares: usize) -> EnsembleStatistics {
+            let shares: usize = shares.clamp(1, self.len().max(1));
+            let chunks: Vec<&[(S, Vec<f64>)]> = self.members.chunks(self.len().div_ceil(shares).max(1)).collect();
+
+            // each thread accumulates running means and squared deviations over its share of the members
+            let partials: Vec<RunningMoments> = parallel_map(&chunks, |chunk| {
+                let mut moments: RunningMoments = RunningMoments::default();
+                for (system, state) in chunk.iter() {
+                    let stream = TrajectoryStream::new(system, integrator, dt)
+                        .with_initial_state(state.clone())
+                        .with_steps(steps)
+                        .with_sampling(sampling);
+                    moments.count += 1.0;
+                    for (k, (t, point)) in stream.enumerate() {
+                        if k == moments.mean.len() {
+                            moments.times.push(t);
+                            moments.mean.push(vec![0.0; point.len()]);
+                            moments.squared_deviations.push(vec![0.0; point.len()]);
+                        }
+                        for (i, value) in point.iter().enumerate() {
+                            let delta: f64 = value - moments.mean[k][i];
+                            moments.mean[k][i] += delta / moments.count;
+                            moments.squared_deviations[k][i] += delta * (value - moments.mean[k][i]);
+                        }
+                    }
+                }
+                moments
+            });
+
+            let mut total: RunningMoments = RunningMoments::default();
+            for partial in partials {
+                if total.count == 0.0 {
+                    total = partial;
+                    continue;
+                }
+                let count: f64 = total.count + partial.count;
+                for k in 0..total.mean.len() {
+                    for i in 0..total.mean[k].len() {
+                        let delta: f64 = partial.mean[k][i] - total.mean[k][i];
+                        total.mean[k][i] += delta * partial.count / count;
+                        total.squared_deviations[k][i] += partial.squared_deviations[k][i]
+                            + delta * delta * total.count * partial.count / count;
+                    }
+                }
+                total.count = count;
+            }
+
+            let RunningMoments { count, times, mean, squared_deviations } = total;
+            let variance: Vec<Vec<f64>> = squared_deviations
+                .iter()
+                .map(|row| row.iter().map(|m2| m2 / count).collect())
+                .collect();
+
+            EnsembleStatistics {
+                times,
+                mean,
+                variance
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::double_pendulum::double_pendulum::DoublePendulum;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        /// Asserts that the statistics merged from several shares match the mean and population variance of the member trajectories
+        fn assert_matches_trajectories<S: DynamicalSystem + Sync>(ensemble: &Ensemble<S>, integrator: Integrator, dt: f64, steps: usize, sampling: Sampling) {
+            let trajectories: Vec<Trajectory> = ensemble.trajectories(integrator, dt, steps, sampling);
+            let count: f64 = trajectories.len() as f64;
+
+            for shares in [1, 2, 5] {
+                let statistics: EnsembleStatistics = ensemble.statistics_in_shares(integrator, dt, steps, sampling, shares);
+                assert_eq!(statistics.times, trajectories[0].times);
+                for k in 0..statistics.times.len() {
+                    for i in 0..trajectories[0].dimension() {
+                        let values: Vec<f64> = trajectories.iter().map(|trajectory| trajectory.column(i)[k]).collect();
+                        let mean: f64 = values.iter().sum::<f64>() / count;
+                        let variance: f64 = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count;
+                        assert!((statistics.mean[k][i] - mean).abs() <= 1e-10 * mean.abs().max(1.0), "mean at {}, {} over {} shares", k, i, shares);
+                        assert!((statistics.variance[k][i] - variance).abs() <= 1e-10 * variance.max(1.0), "variance at {}, {} over {} shares", k, i, shares);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn merged_statistics_match_the_member_trajectories() {
+            let lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 0);
+            assert_matches_trajectories(&Ensemble::perturbed(lorenz, 37, 0.5, 1), Integrator::RungeKutta4, 0.01, 1500, Sampling::new(2.0, 7));
+
+            let pendulum: DoublePendulum = DoublePendulum::new(1.0, 2.0, 0.0, 0.0, 0.01, 9.81, 0);
+            assert_matches_trajectories(&Ensemble::perturbed(pendulum, 23, 0.01, 2), Integrator::RungeKutta4, 0.001, 3000, Sampling::new(0.0, 10));
+        }
+    }
+}
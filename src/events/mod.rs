@@ -0,0 +1,174 @@
+pub mod events {
+    const MAX_ROOT_ITERATIONS: usize = 100;
+
+    /// Direction of the zero crossings of an event function that trigger the event
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Direction {
+        /// The event function goes from negative to non-negative
+        Rising,
+        /// The event function goes from positive to non-positive
+        Falling,
+        /// Either of the above
+        Either
+    }
+
+    /// Event function g(t, state)
+    pub type EventFunction = Box<dyn Fn(f64, &[f64]) -> f64>;
+
+    /// A scalar function g(t, state) whose zero crossings are located during integration
+    pub struct Event {
+        function: EventFunction,
+        pub direction: Direction,
+        pub terminal: bool
+    }
+
+    impl Event {
+        /// Creates a non-terminal Event triggered by crossings in either direction
+        ///
+        /// *Params
+        ///
+        /// function: event function g(t, state). The event occurs where it crosses zero
+        pub fn new<F: Fn(f64, &[f64]) -> f64 + 'static>(function: F) -> Event {
+            Event {
+                function: Box::new(function),
+                direction: Direction::Either,
+                terminal: false
+            }
+        }
+
+        /// Restricts the event to crossings in one direction
+        pub fn with_direction(mut self, direction: Direction) -> Event {
+            self.direction = direction;
+            self
+        }
+
+        /// Makes the event stop the integration the first time it occurs
+        pub fn terminal(mut self) -> Event {
+            self.terminal = true;
+            self
+        }
+
+        /// Evaluates the event function
+        pub fn value(&self, t: f64, state: &[f64]) -> f64 {
+            (self.function)(t, state)
+        }
+
+        /// Returns whether going from the value `before` to the value `after` is a crossing that triggers the event
+        pub fn is_triggered(&self, before: f64, after: f64) -> bool {
+            let rising: bool = before < 0.0 && after >= 0.0;
+            let falling: bool = before > 0.0 && after <= 0.0;
+            match self.direction {
+                Direction::Rising => rising,
+                Direction::Falling => falling,
+                Direction::Either => rising || falling
+            }
+        }
+    }
+
+    /// An occurrence of an event located during integration
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct EventRecord {
+        /// Index of the event in the list passed to the solver
+        pub event: usize,
+        pub t: f64,
+        pub state: Vec<f64>
+    }
+
+    /// Locates a root of g inside [a, b], where g(a) and g(b) have opposite signs, with the Illinois
+    /// variant of regula falsi. Returns the end of the final bracket on the side of b
+    ///
+    /// *Params
+    ///
+    /// g: continuous function of time
+    ///
+    /// a, g_a: start of the bracket and the value of g there
+    ///
+    /// b, g_b: end of the bracket and the value of g there
+    pub fn locate_root<G: Fn(f64) -> f64>(g: G, mut a: f64, mut g_a: f64, mut b: f64, mut g_b: f64) -> f64 {
+        let tolerance: f64 = 4.0 * f64::EPSILON * a.abs().max(b.abs()).max(1.0);
+
+        for _ in 0..MAX_ROOT_ITERATIONS {
+            if g_b == 0.0 || (b - a).abs() <= tolerance {
+                break;
+            }
+            let c: f64 = b - g_b * (b - a) / (g_b - g_a);
+            let g_c: f64 = g(c);
+            if g_c * g_b < 0.0 {
+                a = b;
+                g_a = g_b;
+            } else {
+                g_a *= 0.5;
+            }
+            b = c;
+            g_b = g_c;
+        }
+
+        b
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::f64::consts::PI;
+        use crate::adaptive::adaptive::DormandPrince;
+        use crate::dynamical_system::dynamical_system::test_systems::HarmonicOscillator;
+        use crate::integrators::integrators::{OdeSolver, Solution};
+
+        /// Returns the records of the zero crossings of x for the oscillator (cos t, -sin t) over [0, 10]
+        fn crossings(event: Event) -> (Solution, Vec<EventRecord>) {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            DormandPrince::new(1e-10, 1e-10).solve_with_events(&system, 0.0, &[1.0, 0.0], 10.0, &[event]).unwrap()
+        }
+
+        fn times(records: &[EventRecord]) -> Vec<f64> {
+            records.iter().map(|record| record.t).collect()
+        }
+
+        fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+            assert_eq!(actual.len(), expected.len(), "{:?} against {:?}", actual, expected);
+            for (a, b) in actual.iter().zip(expected.iter()) {
+                assert!((a - b).abs() < tolerance, "{:?} against {:?}", actual, expected);
+            }
+        }
+
+        #[test]
+        fn illinois_locates_known_roots() {
+            let root: f64 = locate_root(|t| t.cos(), 0.0, 1.0, 3.0, 3.0_f64.cos());
+            assert!((root - PI / 2.0).abs() < 1e-14);
+            let root: f64 = locate_root(|t| t.powi(3) - 2.0, 0.0, -2.0, 2.0, 6.0);
+            assert!((root - 2.0_f64.cbrt()).abs() < 1e-14);
+        }
+
+        #[test]
+        fn crossing_times_match_the_analytic_zeros() {
+            let (_, records) = crossings(Event::new(|_, state| state[0]));
+            assert_close(&times(&records), &[PI / 2.0, 3.0 * PI / 2.0, 5.0 * PI / 2.0], 1e-8);
+            assert!(records.iter().all(|record| record.state[0].abs() < 1e-8 && record.event == 0));
+        }
+
+        #[test]
+        fn direction_filters_the_crossings() {
+            let (_, falling) = crossings(Event::new(|_, state| state[0]).with_direction(Direction::Falling));
+            assert_close(&times(&falling), &[PI / 2.0, 5.0 * PI / 2.0], 1e-8);
+            let (_, rising) = crossings(Event::new(|_, state| state[0]).with_direction(Direction::Rising));
+            assert_close(&times(&rising), &[3.0 * PI / 2.0], 1e-8);
+        }
+
+        #[test]
+        fn terminal_event_stops_the_integration() {
+            let (solution, records) = crossings(Event::new(|_, state| state[0]).with_direction(Direction::Rising).terminal());
+            assert_close(&times(&records), &[3.0 * PI / 2.0], 1e-8);
+            assert_eq!(*solution.times.last().unwrap(), records[0].t);
+            assert_eq!(*solution.states.last().unwrap(), records[0].state);
+        }
+
+        #[test]
+        fn locating_events_alone_finds_the_same_records() {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            let events: [Event; 1] = [Event::new(|_, state| state[0])];
+            let (_, records) = crossings(Event::new(|_, state| state[0]));
+            let located: Vec<EventRecord> = DormandPrince::new(1e-10, 1e-10).locate_events(&system, 0.0, &[1.0, 0.0], 10.0, &events).unwrap();
+            assert_eq!(located, records);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+pub mod extrema {
+    use plotters::prelude::*;
+    use crate::trajectory::trajectory::Trajectory;
+
+    /// Whether an extremum is a peak or a trough
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ExtremumKind {
+        Maximum,
+        Minimum
+    }
+
+    /// Local extremum of a sampled time series, refined between the samples
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Extremum {
+        pub kind: ExtremumKind,
+        /// Index of the sample closest to the extremum
+        pub index: usize,
+        /// Time of the vertex of the parabola through the sample and its two neighbours
+        pub t: f64,
+        /// Value at the vertex of the parabola
+        pub value: f64
+    }
+
+    /// Returns the local extrema of a sampled time series in time order. Each one is refined by fitting
+    /// a parabola through the extreme sample and its two neighbours, so peaks are located between the
+    /// samples rather than at the nearest one
+    ///
+    /// *Params
+    ///
+    /// times: time of each sample
+    ///
+    /// values: value of each sample
+    pub fn local_extrema(times: &[f64], values: &[f64]) -> Vec<Extremum> {
+        assert_eq!(times.len(), values.len(), "times and values must have the same length");
+        let mut extrema: Vec<Extremum> = Vec::new();
+
+        for i in 1..values.len().saturating_sub(1) {
+            let (before, value, after): (f64, f64, f64) = (values[i - 1], values[i], values[i + 1]);
+            let kind: ExtremumKind = if before < value && value >= after {
+                ExtremumKind::Maximum
+            } else if before > value && value <= after {
+                ExtremumKind::Minimum
+            } else {
+                continue;
+            };
+
+            let (t, value): (f64, f64) = parabola_vertex(
+                (times[i - 1] - times[i], before),
+                (0.0, value),
+                (times[i + 1] - times[i], after)
+            );
+            extrema.push(Extremum {
+                kind,
+                index: i,
+                t: times[i] + t,
+                value
+            });
+        }
+
+        extrema
+    }
+
+    /// Returns the local maxima of a sampled time series, refined between the samples
+    pub fn local_maxima(times: &[f64], values: &[f64]) -> Vec<Extremum> {
+        local_extrema(times, values).into_iter().filter(|extremum| extremum.kind == ExtremumKind::Maximum).collect()
+    }
+
+    /// Returns the local minima of a sampled time series, refined between the samples
+    pub fn local_minima(times: &[f64], values: &[f64]) -> Vec<Extremum> {
+        local_extrema(times, values).into_iter().filter(|extremum| extremum.kind == ExtremumKind::Minimum).collect()
+    }
+
+    /// Returns the local extrema of one state variable of a trajectory
+    pub fn trajectory_extrema(trajectory: &Trajectory, variable: usize) -> Vec<Extremum> {
+        local_extrema(&trajectory.times, trajectory.column(variable))
+    }
+
+    /// Returns the vertex of the parabola through three points, with the middle one at x = 0. When the
+    /// points are collinear the middle point is returned
+    fn parabola_vertex(left: (f64, f64), middle: (f64, f64), right: (f64, f64)) -> (f64, f64) {
+        let (x0, y0): (f64, f64) = left;
+        let (_, y1): (f64, f64) = middle;
+        let (x2, y2): (f64, f64) = right;
+
+        // y = a x^2 + b x + y1 through the three points
+        let slope_left: f64 = (y1 - y0) / -x0;
+        let slope_right: f64 = (y2 - y1) / x2;
+        let a: f64 = (slope_right - slope_left) / (x2 - x0);
+        if a == 0.0 || !a.is_finite() {
+            return middle;
+        }
+        let b: f64 = slope_left - a * x0;
+        let x: f64 = (-b / (2.0 * a)).clamp(x0, x2);
+        (x, a * x * x + b * x + y1)
+    }
+
+    /// Return map x_n -> x_(n+1) of successive values of a sequence, such as the successive maxima
+    /// of a state variable in Lorenz's map of the z maxima
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ReturnMap {
+        pub variable_name: String,
+        /// Pairs (x_n, x_(n+1))
+        pub points: Vec<(f64, f64)>
+    }
+
+    impl ReturnMap {
+        /// Creates the ReturnMap of a sequence of values
+        pub fn from_values(variable_name: &str, values: &[f64]) -> ReturnMap {
+            ReturnMap {
+                variable_name: variable_name.to_string(),
+                points: values.windows(2).map(|pair| (pair[0], pair[1])).collect()
+            }
+        }
+
+        /// Creates the next-maximum map of one state variable of a trajectory
+        pub fn next_maximum(trajectory: &Trajectory, variable: usize) -> ReturnMap {
+            let maxima: Vec<f64> = local_maxima(&trajectory.times, trajectory.column(variable)).iter().map(|maximum| maximum.value).collect();
+            ReturnMap::from_values(&trajectory.variable_names[variable], &maxima)
+        }
+
+        /// Creates the next-minimum map of one state variable of a trajectory
+        pub fn next_minimum(trajectory: &Trajectory, variable: usize) -> ReturnMap {
+            let minima: Vec<f64> = local_minima(&trajectory.times, trajectory.column(variable)).iter().map(|minimum| minimum.value).collect();
+            ReturnMap::from_values(&trajectory.variable_names[variable], &minima)
+        }
+
+        /// Plots the return map together with the diagonal x_(n+1) = x_n
+        ///
+        /// *Params
+        ///
+        /// path: file the PNG is written to
+        ///
+        /// size: (width, height) of the image in pixels
+        pub fn display(&self, path: &str, size: (u32, u32)) {
+            let (low, high): (f64, f64) = self
+                .points
+                .iter()
+                .flat_map(|&(x, y)| [x, y])
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)));
+            let (low, high): (f64, f64) = if low.is_finite() && high > low {
+                let margin: f64 = 0.05 * (high - low);
+                (low - margin, high + margin)
+            } else {
+                (0.0, 1.0)
+            };
+
+            let root: DrawingArea<BitMapBackend<'_>, plotters::coord::Shift> = BitMapBackend::new(path, size).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+
+            let mut chart = ChartBuilder::on(&root)
+                .caption(format!("Return Map of {}", self.variable_name), ("Arial", 20).into_font())
+                .margin(5)
+                .x_label_area_size(40)
+                .y_label_area_size(50)
+                .build_cartesian_2d(low..high, low..high)
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .x_desc(format!("{}_n", self.variable_name))
+                .y_desc(format!("{}_n+1", self.variable_name))
+                .draw()
+                .unwrap();
+
+            chart
+                .draw_series(LineSeries::new(vec![(low, low), (high, high)], &GREEN))
+                .unwrap();
+
+            chart
+                .draw_series(self.points.iter().map(|&point| Circle::new(point, 2, RED.filled())))
+                .unwrap();
+
+            root.present().unwrap();
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::f64::consts::PI;
+        use crate::integrators::integrators::Integrator;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        #[test]
+        fn sine_extrema_are_located_between_the_samples() {
+            let times: Vec<f64> = (0..=200).map(|i| i as f64 * 0.1).collect();
+            let values: Vec<f64> = times.iter().map(|t| t.sin()).collect();
+            let extrema: Vec<Extremum> = local_extrema(&times, &values);
+
+            // extrema of sin alternate between maxima at pi / 2 + 2 k pi and minima at 3 pi / 2 + 2 k pi
+            assert_eq!(extrema.len(), 6);
+            for (k, extremum) in extrema.iter().enumerate() {
+                let (kind, value): (ExtremumKind, f64) = if k % 2 == 0 { (ExtremumKind::Maximum, 1.0) } else { (ExtremumKind::Minimum, -1.0) };
+                assert_eq!(extremum.kind, kind);
+                assert!((extremum.t - (PI / 2.0 + k as f64 * PI)).abs() < 1e-3, "{:?}", extremum);
+                assert!((extremum.value - value).abs() < 1e-4, "{:?}", extremum);
+                assert!((times[extremum.index] - extremum.t).abs() <= 0.05);
+            }
+            assert_eq!(local_maxima(&times, &values).len(), 3);
+            assert_eq!(local_minima(&times, &values).len(), 3);
+        }
+
+        #[test]
+        fn constant_and_monotone_signals_have_no_extrema() {
+            let times: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+            let constant: Vec<f64> = vec![2.0; 100];
+            let increasing: Vec<f64> = times.iter().map(|t| t.exp()).collect();
+            let decreasing: Vec<f64> = times.iter().map(|t| -t).collect();
+            for values in [constant, increasing, decreasing] {
+                assert!(local_extrema(&times, &values).is_empty());
+            }
+            assert!(local_extrema(&[], &[]).is_empty());
+            assert!(local_extrema(&[0.0, 1.0], &[1.0, 0.0]).is_empty());
+        }
+
+        #[test]
+        fn lorenz_return_map_pairs_successive_maxima() {
+            let mut lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 10000).with_integrator(Integrator::RungeKutta4);
+            let trajectory: Trajectory = lorenz.simulate_trajectory();
+            let maxima: Vec<Extremum> = local_maxima(&trajectory.times, trajectory.column(2));
+            let map: ReturnMap = ReturnMap::next_maximum(&trajectory, 2);
+
+            assert!(maxima.len() > 50);
+            assert_eq!(map.variable_name, "z");
+            assert_eq!(map.points.len(), maxima.len() - 1);
+            for (k, &(current, next)) in map.points.iter().enumerate() {
+                assert_eq!((current, next), (maxima[k].value, maxima[k + 1].value));
+            }
+        }
+    }
+}
@@ -0,0 +1,537 @@
+pub mod fractal_dimension {
+    use std::collections::HashMap;
+    use crate::ensemble::ensemble::parallel_map;
+
+    /// Number of interleaved blocks of rows the pair loops are split into, so that the
+    /// triangular workload is shared evenly between threads
+    const PAIR_BLOCKS: usize = 64;
+
+    /// Smallest average number of points per occupied box for a grid to be well sampled. Finer grids
+    /// hold about one point per box and their counts saturate at the number of points
+    const MIN_POINTS_PER_BOX: f64 = 10.0;
+
+    /// Largest box size, as a fraction of the extent of the cloud, for a grid to resolve its shape.
+    /// Coarser grids are dominated by how the cloud happens to sit against the box edges
+    const MAX_SIZE_FRACTION: f64 = 0.1;
+
+    /// Collects the columns of a simulation, such as the vectors returned by `simulate`, into points
+    pub fn points_from_columns(columns: &[&[f64]]) -> Vec<Vec<f64>> {
+        let length: usize = columns.iter().map(|column| column.len()).min().unwrap_or(0);
+        (0..length).map(|i| columns.iter().map(|column| column[i]).collect()).collect()
+    }
+
+    /// Least squares line through a set of points of a log-log curve, with the range of the
+    /// abscissa it was fitted over
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct DimensionFit {
+        /// Slope of the line, which is the dimension estimate
+        pub slope: f64,
+        pub intercept: f64,
+        /// Coefficient of determination of the fit
+        pub r_squared: f64,
+        /// Range of the scale (radius or box size) the line was fitted over
+        pub scaling_region: (f64, f64)
+    }
+
+    /// Fits a line to the points of a log-log curve whose scale lies in the given region, or in the
+    /// region chosen by `find_scaling_region` when none is given
+    ///
+    /// *Params
+    ///
+    /// scales: radius or box size of each point of the curve
+    ///
+    /// log_scales, log_values: the curve
+    ///
+    /// region: (smallest, largest) scale to fit over
+    fn fit_curve(scales: &[f64], log_scales: &[f64], log_values: &[f64], region: Option<(f64, f64)>) -> Option<DimensionFit> {
+        let (start, end): (usize, usize) = match region {
+            Some((low, high)) => {
+                let indices: Vec<usize> = (0..scales.len()).filter(|&i| scales[i] >= low && scales[i] <= high).collect();
+                (*indices.first()?, *indices.last()? + 1)
+            }
+            None => find_scaling_region(log_scales, log_values)?
+        };
+        if end - start < 2 {
+            return None;
+        }
+
+        let (slope, intercept, r_squared): (f64, f64, f64) = linear_fit(&log_scales[start..end], &log_values[start..end]);
+        Some(DimensionFit {
+            slope,
+            intercept,
+            r_squared,
+            scaling_region: (scales[start], scales[end - 1])
+        })
+    }
+
+    /// Returns the index range of the longest stretch of a log-log curve over which the local slopes
+    /// stay within 20% of each other, falling back to the most uniform stretch of minimal length
+    fn find_scaling_region(log_scales: &[f64], log_values: &[f64]) -> Option<(usize, usize)> {
+        let n: usize = log_scales.len();
+        if n < 2 {
+            return None;
+        }
+        let slopes: Vec<f64> = (0..n - 1)
+            .map(|i| (log_values[i + 1] - log_values[i]) / (log_scales[i + 1] - log_scales[i]))
+            .collect();
+        let minimum_length: usize = (slopes.len() / 3).max(2).min(slopes.len());
+
+        let mut best: Option<(usize, usize, f64)> = None;
+        for start in 0..slopes.len() {
+            for end in start + minimum_length..=slopes.len() {
+                let window: &[f64] = &slopes[start..end];
+                let low: f64 = window.iter().cloned().fold(f64::INFINITY, f64::min);
+                let high: f64 = window.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let mean: f64 = window.iter().sum::<f64>() / window.len() as f64;
+                let spread: f64 = (high - low) / mean.abs().max(f64::MIN_POSITIVE);
+
+                let better: bool = match best {
+                    None => true,
+                    Some((best_start, best_end, best_spread)) => {
+                        let best_within: bool = best_spread <= 0.2;
+                        let within: bool = spread <= 0.2;
+                        match (within, best_within) {
+                            (true, false) => true,
+                            (false, true) => false,
+                            (true, true) => end - start > best_end - best_start || (end - start == best_end - best_start && spread < best_spread),
+                            (false, false) => spread < best_spread
+                        }
+                    }
+                };
+                if better {
+                    best = Some((start, end, spread));
+                }
+            }
+        }
+
+        // a window of k local slopes spans k + 1 points of the curve
+        best.map(|(start, end, _)| (start, end + 1))
+    }
+
+    /// Returns the slope, intercept and coefficient of determination of the least squares line through (x, y)
+    fn linear_fit(x: &[f64], y: &[f64]) -> (f64, f64, f64) {
+        let n: f64 = x.len() as f64;
+        let mean_x: f64 = x.iter().sum::<f64>() / n;
+        let mean_y: f64 = y.iter().sum::<f64>() / n;
+        let sxx: f64 = x.iter().map(|xi| (xi - mean_x).powi(2)).sum();
+        let sxy: f64 = x.iter().zip(y.iter()).map(|(xi, yi)| (xi - mean_x) * (yi - mean_y)).sum();
+        let syy: f64 = y.iter().map(|yi| (yi - mean_y).powi(2)).sum();
+
+        let slope: f64 = sxy / sxx;
+        let intercept: f64 = mean_y - slope * mean_x;
+        let r_squared: f64 = if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) };
+        (slope, intercept, r_squared)
+    }
+
+    /// Returns `count` radii or box sizes spaced evenly in log scale from r_min to r_max
+    pub fn log_spaced(r_min: f64, r_max: f64, count: usize) -> Vec<f64> {
+        assert!(r_min > 0.0 && r_max > r_min, "radii must satisfy 0 < r_min < r_max");
+        assert!(count >= 2, "at least two radii are needed");
+        let ratio: f64 = (r_max / r_min).ln() / (count - 1) as f64;
+        (0..count).map(|i| r_min * (ratio * i as f64).exp()).collect()
+    }
+
+    /// Correlation sums C(r) of a point cloud over a range of radii
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct CorrelationSum {
+        pub radii: Vec<f64>,
+        /// Fraction of the counted pairs closer than each radius
+        pub sums: Vec<f64>,
+        /// Number of pairs counted, after excluding the Theiler window
+        pub pairs: usize
+    }
+
+    impl CorrelationSum {
+        /// Returns the (log r, log C(r)) curve, leaving out the radii no pair is closer than
+        pub fn log_log(&self) -> Vec<(f64, f64)> {
+            self.radii
+                .iter()
+                .zip(self.sums.iter())
+                .filter(|(_, &sum)| sum > 0.0)
+                .map(|(radius, sum)| (radius.ln(), sum.ln()))
+                .collect()
+        }
+
+        /// Fits the slope of the log-log curve, which estimates the correlation dimension
+        ///
+        /// *Params
+        ///
+        /// scaling_region: (smallest, largest) radius to fit over. When None the longest stretch
+        /// of nearly constant slope is chosen
+        pub fn fit(&self, scaling_region: Option<(f64, f64)>) -> Option<DimensionFit> {
+            let radii: Vec<f64> = self.radii.iter().zip(self.sums.iter()).filter(|(_, &sum)| sum > 0.0).map(|(radius, _)| *radius).collect();
+            let (log_radii, log_sums): (Vec<f64>, Vec<f64>) = self.log_log().into_iter().unzip();
+            fit_curve(&radii, &log_radii, &log_sums, scaling_region)
+        }
+    }
+
+    /// Grassberger-Procaccia estimator of the correlation dimension of a point cloud, such as the
+    /// points of an attractor sampled from a trajectory
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct CorrelationDimension {
+        /// Radii the correlation sum is evaluated at, in increasing order
+        pub radii: Vec<f64>,
+        /// Pairs of points fewer than this many samples apart are ignored, since they are close
+        /// only because they are neighbours along the trajectory
+        pub theiler_window: usize,
+        /// When set, the cloud is thinned evenly to at most this many points before the pairs are counted
+        pub max_points: Option<usize>,
+        /// Radii to fit the slope over. When None the scaling region is chosen automatically
+        pub scaling_region: Option<(f64, f64)>
+    }
+
+    impl CorrelationDimension {
+        /// Creates a CorrelationDimension estimator with log spaced radii, no Theiler window and all points used
+        ///
+        /// *Params
+        ///
+        /// r_min: smallest radius
+        ///
+        /// r_max: largest radius
+        ///
+        /// count: number of radii
+        pub fn new(r_min: f64, r_max: f64, count: usize) -> CorrelationDimension {
+            CorrelationDimension {
+                radii: log_spaced(r_min, r_max, count),
+                theiler_window: 0,
+                max_points: None,
+                scaling_region: None
+            }
+        }
+
+        /// Replaces the radii
+        pub fn with_radii(mut self, mut radii: Vec<f64>) -> CorrelationDimension {
+            assert!(radii.iter().all(|&r| r > 0.0), "radii must be positive");
+            radii.sort_by(|a, b| a.total_cmp(b));
+            self.radii = radii;
+            self
+        }
+
+        /// Ignores pairs of points whose indices differ by at most the given number of samples
+        pub fn with_theiler_window(mut self, window: usize) -> CorrelationDimension {
+            self.theiler_window = window;
+            self
+        }
+
+        /// Thins the cloud evenly to at most the given number of points, since the cost grows with its square
+        pub fn with_max_points(mut self, max_points: usize) -> CorrelationDimension {
+            assert!(max_points >= 2, "at least two points are needed");
+            self.max_points = Some(max_points);
+            self
+        }
+
+        /// Fits the slope over the given range of radii instead of choosing it automatically
+        pub fn with_scaling_region(mut self, r_low: f64, r_high: f64) -> CorrelationDimension {
+            assert!(r_low < r_high, "scaling region must satisfy r_low < r_high");
+            self.scaling_region = Some((r_low, r_high));
+            self
+        }
+
+        /// Computes the correlation sums of a point cloud, counting the pairs on all available CPU cores
+        pub fn correlation_sum(&self, points: &[Vec<f64>]) -> CorrelationSum {
+            let stride: usize = match self.max_points {
+                Some(max_points) if points.len() > max_points => points.len().div_ceil(max_points),
+                _ => 1
+            };
+            let sample: Vec<&Vec<f64>> = points.iter().step_by(stride).collect();
+            // the window is given in samples of the original cloud
+            let window: usize = self.theiler_window.div_ceil(stride);
+            let squared_radii: Vec<f64> = self.radii.iter().map(|r| r * r).collect();
+
+            let blocks: Vec<usize> = (0..PAIR_BLOCKS.min(sample.len().max(1))).collect();
+            let histograms: Vec<(Vec<usize>, usize)> = parallel_map(&blocks, |&block| {
+                let mut histogram: Vec<usize> = vec![0; squared_radii.len() + 1];
+                let mut pairs: usize = 0;
+                for i in (block..sample.len()).step_by(blocks.len()) {
+                    for j in i + window + 1..sample.len() {
+                        let distance: f64 = sample[i].iter().zip(sample[j].iter()).map(|(a, b)| (a - b).powi(2)).sum();
+                        histogram[squared_radii.partition_point(|&r| r <= distance)] += 1;
+                        pairs += 1;
+                    }
+                }
+                (histogram, pairs)
+            });
+
+            let mut histogram: Vec<usize> = vec![0; squared_radii.len() + 1];
+            let mut pairs: usize = 0;
+            for (block_histogram, block_pairs) in histograms {
+                for (total, count) in histogram.iter_mut().zip(block_histogram) {
+                    *total += count;
+                }
+                pairs += block_pairs;
+            }
+
+            // a pair in bin k is closer than radii[k] and every larger radius
+            let mut closer: usize = 0;
+            let sums: Vec<f64> = histogram[..self.radii.len()]
+                .iter()
+                .map(|count| {
+                    closer += count;
+                    if pairs == 0 { 0.0 } else { closer as f64 / pairs as f64 }
+                })
+                .collect();
+
+            CorrelationSum {
+                radii: self.radii.clone(),
+                sums,
+                pairs
+            }
+        }
+
+        /// Estimates the correlation dimension of a point cloud. Returns None when fewer than two
+        /// radii have a nonzero correlation sum
+        pub fn estimate(&self, points: &[Vec<f64>]) -> Option<DimensionFit> {
+            self.correlation_sum(points).fit(self.scaling_region)
+        }
+    }
+
+    /// Occupied boxes and their entropy for a point cloud covered by grids of several box sizes
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct BoxCounts {
+        /// Edge length of the boxes of each grid, in increasing order
+        pub sizes: Vec<f64>,
+        /// Number of boxes holding at least one point, for each size
+        pub occupied: Vec<usize>,
+        /// Shannon entropy -sum p ln p of the fraction p of the points in each box, for each size
+        pub entropies: Vec<f64>,
+        /// Number of points covered
+        pub points: usize,
+        /// Largest extent of the cloud along any coordinate
+        pub extent: f64
+    }
+
+    impl BoxCounts {
+        /// Returns the (ln 1/size, ln N(size)) curve, whose slope is the box-counting dimension
+        pub fn log_counts(&self) -> Vec<(f64, f64)> {
+            self.sizes.iter().zip(self.occupied.iter()).map(|(size, &count)| (-size.ln(), (count as f64).ln())).collect()
+        }
+
+        /// Returns the (ln 1/size, I(size)) curve, whose slope is the information dimension
+        pub fn log_entropies(&self) -> Vec<(f64, f64)> {
+            self.sizes.iter().zip(self.entropies.iter()).map(|(size, &entropy)| (-size.ln(), entropy)).collect()
+        }
+
+        /// Returns whether the grid of each size is fine enough to resolve the shape of the cloud and
+        /// coarse enough to be well sampled by its points
+        pub fn resolved(&self) -> Vec<bool> {
+            self.sizes
+                .iter()
+                .zip(self.occupied.iter())
+                .map(|(&size, &count)| {
+                    count > 0 && self.points as f64 / count as f64 >= MIN_POINTS_PER_BOX && size <= MAX_SIZE_FRACTION * self.extent
+                })
+                .collect()
+        }
+
+        /// Fits the slope of the box count curve, which estimates the box-counting (capacity) dimension.
+        /// Returns None for fewer than two points
+        ///
+        /// *Params
+        ///
+        /// scaling_region: (smallest, largest) box size to fit over. When None the longest stretch
+        /// of nearly constant slope among the resolved sizes is chosen
+        pub fn fit_capacity(&self, scaling_region: Option<(f64, f64)>) -> Option<DimensionFit> {
+            let (log_inverse_sizes, log_counts): (Vec<f64>, Vec<f64>) = self.log_counts().into_iter().unzip();
+            self.fit(&log_inverse_sizes, &log_counts, scaling_region)
+        }
+
+        /// Fits the slope of the entropy curve, which estimates the information dimension. Returns None
+        /// for fewer than two points
+        ///
+        /// *Params
+        ///
+        /// scaling_region: (smallest, largest) box size to fit over. When None the longest stretch
+        /// of nearly constant slope among the resolved sizes is chosen
+        pub fn fit_information(&self, scaling_region: Option<(f64, f64)>) -> Option<DimensionFit> {
+            let (log_inverse_sizes, entropies): (Vec<f64>, Vec<f64>) = self.log_entropies().into_iter().unzip();
+            self.fit(&log_inverse_sizes, &entropies, scaling_region)
+        }
+
+        /// Fits a curve over the given region, or over the automatically chosen part of the resolved sizes
+        fn fit(&self, log_inverse_sizes: &[f64], values: &[f64], scaling_region: Option<(f64, f64)>) -> Option<DimensionFit> {
+            if self.points < 2 {
+                return None;
+            }
+            if scaling_region.is_some() {
+                return fit_curve(&self.sizes, log_inverse_sizes, values, scaling_region);
+            }
+
+            let resolved: Vec<bool> = self.resolved();
+            let start: usize = resolved.iter().position(|&r| r)?;
+            let end: usize = resolved.iter().rposition(|&r| r)? + 1;
+            fit_curve(&self.sizes[start..end], &log_inverse_sizes[start..end], &values[start..end], None)
+        }
+    }
+
+    /// Box-counting (capacity) and information dimension estimator of a point cloud in any dimension.
+    ///
+    /// Each grid is stored as a hash map from the integer coordinates of the occupied boxes to the
+    /// number of points in them, so the memory used grows with the number of occupied boxes rather
+    /// than with the volume of the cloud. The grids of the different sizes are filled in parallel
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct BoxCounting {
+        /// Edge lengths of the boxes, in increasing order
+        pub sizes: Vec<f64>,
+        /// Box sizes to fit the slope over. When None the scaling region is chosen automatically among
+        /// the sizes the cloud resolves
+        pub scaling_region: Option<(f64, f64)>
+    }
+
+    impl BoxCounting {
+        /// Creates a BoxCounting estimator with log spaced box sizes
+        ///
+        /// *Params
+        ///
+        /// size_min: smallest box edge length
+        ///
+        /// size_max: largest box edge length
+        ///
+        /// count: number of box sizes
+        pub fn new(size_min: f64, size_max: f64, count: usize) -> BoxCounting {
+            BoxCounting {
+                sizes: log_spaced(size_min, size_max, count),
+                scaling_region: None
+            }
+        }
+
+        /// Replaces the box sizes
+        pub fn with_sizes(mut self, mut sizes: Vec<f64>) -> BoxCounting {
+            assert!(sizes.iter().all(|&size| size > 0.0), "box sizes must be positive");
+            sizes.sort_by(|a, b| a.total_cmp(b));
+            self.sizes = sizes;
+            self
+        }
+
+        /// Fits the slopes over the given range of box sizes instead of choosing it automatically
+        pub fn with_scaling_region(mut self, size_low: f64, size_high: f64) -> BoxCounting {
+            assert!(size_low < size_high, "scaling region must satisfy size_low < size_high");
+            self.scaling_region = Some((size_low, size_high));
+            self
+        }
+
+        /// Covers a point cloud with a grid of every box size, anchored at the lowest corner of the
+        /// cloud, and counts the occupied boxes and their entropy
+        pub fn counts(&self, points: &[Vec<f64>]) -> BoxCounts {
+            let dimension: usize = points.first().map_or(0, |point| point.len());
+            let origin: Vec<f64> = (0..dimension)
+                .map(|i| points.iter().map(|point| point[i]).fold(f64::INFINITY, f64::min))
+                .collect();
+            let extent: f64 = (0..dimension)
+                .map(|i| points.iter().map(|point| point[i] - origin[i]).fold(0.0, f64::max))
+                .fold(0.0, f64::max);
+
+            let grids: Vec<(usize, f64)> = parallel_map(&self.sizes, |&size| {
+                let mut boxes: HashMap<Vec<i64>, usize> = HashMap::new();
+                let mut key: Vec<i64> = vec![0; dimension];
+                for point in points {
+                    for ((index, value), low) in key.iter_mut().zip(point.iter()).zip(origin.iter()) {
+                        *index = ((value - low) / size).floor() as i64;
+                    }
+                    match boxes.get_mut(key.as_slice()) {
+                        Some(count) => *count += 1,
+                        None => {
+                            boxes.insert(key.clone(), 1);
+                        }
+                    }
+                }
+
+                let total: f64 = points.len() as f64;
+                let entropy: f64 = boxes
+                    .values()
+                    .map(|&count| {
+                        let p: f64 = count as f64 / total;
+                        -p * p.ln()
+                    })
+                    .sum();
+                (boxes.len(), entropy)
+            });
+
+            let (occupied, entropies): (Vec<usize>, Vec<f64>) = grids.into_iter().unzip();
+            BoxCounts {
+                sizes: self.sizes.clone(),
+                occupied,
+                entropies,
+                points: points.len(),
+                extent
+            }
+        }
+
+        /// Estimates the box-counting (capacity) dimension of a point cloud. Returns None for fewer than
+        /// two points, or when too few box sizes resolve the cloud
+        pub fn capacity_dimension(&self, points: &[Vec<f64>]) -> Option<DimensionFit> {
+            self.counts(points).fit_capacity(self.scaling_region)
+        }
+
+        /// Estimates the information dimension of a point cloud. Returns None for fewer than two points,
+        /// or when too few box sizes resolve the cloud
+        pub fn information_dimension(&self, points: &[Vec<f64>]) -> Option<DimensionFit> {
+            self.counts(points).fit_information(self.scaling_region)
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::{Rng, SeedableRng};
+        use crate::integrators::integrators::Integrator;
+        use crate::lorenz::lorenz::LorenzSystem;
+        use crate::seeding::seeding::ChaosRng;
+
+        /// Returns points of the Lorenz attractor sampled every 0.05 after a transient
+        fn lorenz_attractor(points: usize) -> Vec<Vec<f64>> {
+            let mut lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 5000 + 5 * points)
+                .with_integrator(Integrator::RungeKutta4)
+                .with_transient(50.0)
+                .with_stride(5);
+            let (x, y, z): (Vec<f64>, Vec<f64>, Vec<f64>) = lorenz.simulate();
+            points_from_columns(&[&x, &y, &z])
+        }
+
+        #[test]
+        fn correlation_dimension_of_lorenz_matches_the_literature() {
+            let points: Vec<Vec<f64>> = lorenz_attractor(5000);
+            let fit: DimensionFit = CorrelationDimension::new(0.05, 20.0, 30).with_theiler_window(10).estimate(&points).unwrap();
+            assert!((fit.slope - 2.05).abs() < 0.1, "{:?}", fit);
+            assert!(fit.r_squared > 0.999, "{:?}", fit);
+        }
+
+        #[test]
+        fn box_counting_recovers_the_dimension_of_a_line_and_a_square() {
+            let mut rng: ChaosRng = ChaosRng::seed_from_u64(1);
+            let line: Vec<Vec<f64>> = (0..20000).map(|_| rng.gen::<f64>()).map(|t| vec![t, 0.5 * t]).collect();
+            let square: Vec<Vec<f64>> = (0..40000).map(|_| vec![rng.gen::<f64>(), rng.gen::<f64>()]).collect();
+            let estimator: BoxCounting = BoxCounting::new(0.001, 1.0, 31);
+
+            for (points, dimension) in [(line, 1.0), (square, 2.0)] {
+                let capacity: DimensionFit = estimator.capacity_dimension(&points).unwrap();
+                let information: DimensionFit = estimator.information_dimension(&points).unwrap();
+                assert!((capacity.slope - dimension).abs() < 0.1, "{:?}", capacity);
+                assert!((information.slope - dimension).abs() < 0.1, "{:?}", information);
+            }
+        }
+
+        #[test]
+        fn information_dimension_of_lorenz_fits_the_resolved_sizes() {
+            let points: Vec<Vec<f64>> = lorenz_attractor(20000);
+            let fit: DimensionFit = BoxCounting::new(0.05, 20.0, 30).information_dimension(&points).unwrap();
+            assert!(fit.scaling_region.0 > 1.0 && fit.scaling_region.1 < 6.0, "{:?}", fit);
+            assert!((fit.slope - 2.0).abs() < 0.1, "{:?}", fit);
+        }
+
+        #[test]
+        fn box_counting_needs_two_points() {
+            let estimator: BoxCounting = BoxCounting::new(0.1, 1.0, 10);
+            assert_eq!(estimator.capacity_dimension(&[]), None);
+            assert_eq!(estimator.information_dimension(&[vec![0.0, 0.0]]), None);
+            assert_eq!(estimator.with_scaling_region(0.1, 1.0).information_dimension(&[]), None);
+        }
+
+        #[test]
+        fn correlation_dimension_needs_pairs() {
+            let estimator: CorrelationDimension = CorrelationDimension::new(0.1, 1.0, 10);
+            assert_eq!(estimator.estimate(&[]), None);
+            assert_eq!(estimator.estimate(&[vec![0.0, 0.0]]), None);
+        }
+    }
+}
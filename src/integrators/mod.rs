@@ -0,0 +1,492 @@
+pub mod integrators {
+    use std::error::Error;
+    use std::fmt;
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::events::events::{locate_root, Event, EventRecord};
+
+    /// Fixed step integration schemes available to the continuous systems
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Integrator {
+        /// Forward Euler, first order, one vector field evaluation per step
+        #[default]
+        Euler,
+        /// Classic fourth order Runge-Kutta, four vector field evaluations per step
+        RungeKutta4
+    }
+
+    impl Integrator {
+        /// Advances a state of a system by a single step and returns the new state
+        ///
+        /// *Params
+        ///
+        /// system: system whose vector field is integrated
+        ///
+        /// t: time at the start of the step
+        ///
+        /// state: state at the start of the step
+        ///
+        /// dt: size of the step
+        pub fn step<S: DynamicalSystem + ?Sized>(&self, system: &S, t: f64, state: &[f64], dt: f64) -> Vec<f64> {
+            match self {
+                Integrator::Euler => {
+                    let k1: Vec<f64> = system.derivative(t, state);
+                    state.iter().zip(k1.iter()).map(|(y, k)| y + dt * k).collect()
+                }
+                Integrator::RungeKutta4 => {
+                    let k1: Vec<f64> = system.derivative(t, state);
+                    let k2: Vec<f64> = system.derivative(t + 0.5 * dt, &offset(state, &k1, 0.5 * dt));
+                    let k3: Vec<f64> = system.derivative(t + 0.5 * dt, &offset(state, &k2, 0.5 * dt));
+                    let k4: Vec<f64> = system.derivative(t + dt, &offset(state, &k3, dt));
+                    (0..state.len())
+                        .map(|i| state[i] + dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+                        .collect()
+                }
+            }
+        }
+
+        /// Returns the number of vector field evaluations used by a single step
+        pub fn evaluations_per_step(&self) -> usize {
+            match self {
+                Integrator::Euler => 1,
+                Integrator::RungeKutta4 => 4
+            }
+        }
+    }
+
+    /// Returns state + scale * direction
+    fn offset(state: &[f64], direction: &[f64], scale: f64) -> Vec<f64> {
+        state.iter().zip(direction.iter()).map(|(y, d)| y + scale * d).collect()
+    }
+
+    /// Counters collected by a solver over a run
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct SolverStats {
+        pub accepted_steps: usize,
+        pub rejected_steps: usize,
+        pub function_evaluations: usize,
+        pub jacobian_evaluations: usize,
+        pub newton_iterations: usize
+    }
+
+    /// Output of a solver run: the accepted time points, the state at each of them and the run statistics
+    #[derive(Debug, Clone)]
+    pub struct Solution {
+        pub times: Vec<f64>,
+        pub states: Vec<Vec<f64>>,
+        pub stats: SolverStats
+    }
+
+    /// Reasons a solver can fail to reach the end of the integration interval
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum SolverError {
+        /// The error control asked for a step below the solver's minimum step size
+        StepSizeTooSmall { t: f64, step: f64 },
+        /// The iteration solving an implicit stage did not converge for the step of the given size taken from t
+        ImplicitStageDidNotConverge { t: f64, step: f64 }
+    }
+
+    impl fmt::Display for SolverError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SolverError::StepSizeTooSmall { t, step } => {
+                    write!(f, "step size {} fell below the minimum step size at t = {}", step, t)
+                }
+                SolverError::ImplicitStageDidNotConverge { t, step } => {
+                    write!(f, "implicit stage did not converge for the step of size {} at t = {}", step, t)
+                }
+            }
+        }
+    }
+
+    impl Error for SolverError {}
+
+    /// Common interface of the solvers that advance a `DynamicalSystem` through time
+    pub trait OdeSolver {
+        /// Takes a single accepted step from (t, state) without stepping past t_end.
+        /// Returns the time and state at the end of the step
+        fn step(&mut self, system: &dyn DynamicalSystem, t: f64, state: &[f64], t_end: f64) -> Result<(f64, Vec<f64>), SolverError>;
+
+        /// Returns the statistics collected since the last reset
+        fn stats(&self) -> SolverStats;
+
+        /// Clears the statistics and any state carried between steps
+        fn reset(&mut self);
+
+        /// Returns a continuous approximation of the solution over the step just taken from (t0, state0) to (t1, state1)
+        ///
+        /// The default implementation is a cubic Hermite interpolant built from the vector field at both ends of the step.
+        /// Solvers with a more accurate continuous extension of their own override it
+        fn dense_output(&self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t1: f64, state1: &[f64]) -> DenseOutput {
+            let derivative0: Vec<f64> = system.derivative(t0, state0);
+            let derivative1: Vec<f64> = system.derivative(t1, state1);
+            DenseOutput::hermite(t0, state0, &derivative0, t1, state1, &derivative1)
+        }
+
+        /// Integrates a system from t0 to t_end, recording every accepted step
+        ///
+        /// *Params
+        ///
+        /// system: system whose vector field is integrated
+        ///
+        /// t0: start time
+        ///
+        /// state0: state at t0
+        ///
+        /// t_end: end time
+        fn solve(&mut self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t_end: f64) -> Result<Solution, SolverError> {
+            self.reset();
+            let mut times: Vec<f64> = vec![t0];
+            let mut states: Vec<Vec<f64>> = vec![state0.to_vec()];
+            let mut t: f64 = t0;
+            let mut state: Vec<f64> = state0.to_vec();
+
+            while t < t_end {
+                let (t_next, state_next): (f64, Vec<f64>) = self.step(system, t, &state, t_end)?;
+                t = t_next;
+                state = state_next;
+                times.push(t);
+                states.push(state.clone());
+            }
+
+            Ok(Solution {
+                times,
+                states,
+                stats: self.stats()
+            })
+        }
+
+        /// Integrates a system from t0 and records the state at the requested times only,
+        /// interpolating between the solver's own steps with `dense_output`
+        ///
+        /// *Params
+        ///
+        /// system: system whose vector field is integrated
+        ///
+        /// t0: start time
+        ///
+        /// state0: state at t0
+        ///
+        /// times: output times, sorted in increasing order and none before t0. See `uniform_times`
+        fn solve_at(&mut self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], times: &[f64]) -> Result<Solution, SolverError> {
+            assert!(times.windows(2).all(|w| w[0] <= w[1]), "output times must be sorted");
+            assert!(times.first().is_none_or(|&first| first >= t0), "output times must not precede t0");
+
+            self.reset();
+            let mut states: Vec<Vec<f64>> = Vec::with_capacity(times.len());
+            let mut t: f64 = t0;
+            let mut state: Vec<f64> = state0.to_vec();
+
+            while states.len() < times.len() && times[states.len()] == t0 {
+                states.push(state.clone());
+            }
+
+            let t_end: f64 = times.last().copied().unwrap_or(t0);
+            while states.len() < times.len() {
+                let (t_next, state_next): (f64, Vec<f64>) = self.step(system, t, &state, t_end)?;
+                if times[states.len()] <= t_next {
+                    let dense: DenseOutput = self.dense_output(system, t, &state, t_next, &state_next);
+                    while states.len() < times.len() && times[states.len()] <= t_next {
+                        states.push(dense.evaluate(times[states.len()]));
+                    }
+                }
+                t = t_next;
+                state = state_next;
+            }
+
+            Ok(Solution {
+                times: times.to_vec(),
+                states,
+                stats: self.stats()
+            })
+        }
+
+        /// Integrates a system from t0 to t_end while locating the zero crossings of event functions.
+        ///
+        /// Crossings are detected from sign changes between accepted steps and located precisely on
+        /// the `dense_output` of the step. Two crossings of the same event inside one step cancel out
+        /// and are not detected. The integration stops at the first terminal event, which becomes
+        /// the last point of the returned solution
+        ///
+        /// *Params
+        ///
+        /// system: system whose vector field is integrated
+        ///
+        /// t0: start time
+        ///
+        /// state0: state at t0
+        ///
+        /// t_end: end time
+        ///
+        /// events: events to locate, referred to by index in the returned records
+        fn solve_with_events(&mut self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t_end: f64, events: &[Event]) -> Result<(Solution, Vec<EventRecord>), SolverError> {
+            let mut times: Vec<f64> = vec![t0];
+            let mut states: Vec<Vec<f64>> = vec![state0.to_vec()];
+            let records: Vec<EventRecord> = integrate_with_events(self, system, t0, state0, t_end, events, |t, state| {
+                times.push(t);
+                states.push(state.to_vec());
+            })?;
+
+            Ok((Solution { times, states, stats: self.stats() }, records))
+        }
+
+        /// Integrates a system from t0 to t_end like `solve_with_events`, but keeps only the event
+        /// records instead of every step, for long integrations where only the crossings are needed
+        ///
+        /// *Params
+        ///
+        /// system: system whose vector field is integrated
+        ///
+        /// t0: start time
+        ///
+        /// state0: state at t0
+        ///
+        /// t_end: end time
+        ///
+        /// events: events to locate, referred to by index in the returned records
+        fn locate_events(&mut self, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t_end: f64, events: &[Event]) -> Result<Vec<EventRecord>, SolverError> {
+            integrate_with_events(self, system, t0, state0, t_end, events, |_, _| {})
+        }
+    }
+
+    /// Integrates a system while locating event crossings, passing every accepted step to `on_step`.
+    /// A terminal event ends the integration and is passed as the last step
+    fn integrate_with_events<O: OdeSolver + ?Sized, F: FnMut(f64, &[f64])>(solver: &mut O, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t_end: f64, events: &[Event], mut on_step: F) -> Result<Vec<EventRecord>, SolverError> {
+        solver.reset();
+        let mut records: Vec<EventRecord> = Vec::new();
+        let mut t: f64 = t0;
+        let mut state: Vec<f64> = state0.to_vec();
+        let mut values: Vec<f64> = events.iter().map(|event| event.value(t0, state0)).collect();
+
+        while t < t_end {
+            let (t_next, state_next): (f64, Vec<f64>) = solver.step(system, t, &state, t_end)?;
+            let next_values: Vec<f64> = events.iter().map(|event| event.value(t_next, &state_next)).collect();
+            let triggered: Vec<usize> = (0..events.len())
+                .filter(|&i| events[i].is_triggered(values[i], next_values[i]))
+                .collect();
+
+            if !triggered.is_empty() {
+                let dense: DenseOutput = solver.dense_output(system, t, &state, t_next, &state_next);
+                let mut found: Vec<EventRecord> = triggered
+                    .into_iter()
+                    .map(|i| {
+                        let g = |s: f64| events[i].value(s, &dense.evaluate(s));
+                        let t_event: f64 = locate_root(g, t, values[i], t_next, next_values[i]);
+                        EventRecord {
+                            event: i,
+                            t: t_event,
+                            state: dense.evaluate(t_event)
+                        }
+                    })
+                    .collect();
+                found.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+                if let Some(position) = found.iter().position(|record| events[record.event].terminal) {
+                    found.truncate(position + 1);
+                    on_step(found[position].t, &found[position].state);
+                    records.extend(found);
+                    return Ok(records);
+                }
+                records.extend(found);
+            }
+
+            t = t_next;
+            state = state_next;
+            values = next_values;
+            on_step(t, &state);
+        }
+
+        Ok(records)
+    }
+
+    /// Continuous approximation of a solution over a single solver step
+    pub struct DenseOutput {
+        pub t_start: f64,
+        pub t_end: f64,
+        interpolant: Box<dyn Fn(f64) -> Vec<f64>>
+    }
+
+    impl DenseOutput {
+        /// Creates a DenseOutput from an interpolating function valid on [t_start, t_end]
+        pub fn new<F: Fn(f64) -> Vec<f64> + 'static>(t_start: f64, t_end: f64, interpolant: F) -> DenseOutput {
+            DenseOutput {
+                t_start,
+                t_end,
+                interpolant: Box::new(interpolant)
+            }
+        }
+
+        /// Creates the cubic Hermite interpolant matching the states and derivatives at both ends of a step
+        pub fn hermite(t0: f64, state0: &[f64], derivative0: &[f64], t1: f64, state1: &[f64], derivative1: &[f64]) -> DenseOutput {
+            let h: f64 = t1 - t0;
+            let state0: Vec<f64> = state0.to_vec();
+            let state1: Vec<f64> = state1.to_vec();
+            let derivative0: Vec<f64> = derivative0.to_vec();
+            let derivative1: Vec<f64> = derivative1.to_vec();
+
+            DenseOutput::new(t0, t1, move |t| {
+                if h == 0.0 {
+                    return state1.clone();
+                }
+                let theta: f64 = (t - t0) / h;
+                let h00: f64 = (1.0 + 2.0 * theta) * (1.0 - theta).powi(2);
+                let h10: f64 = theta * (1.0 - theta).powi(2);
+                let h01: f64 = theta.powi(2) * (3.0 - 2.0 * theta);
+                let h11: f64 = theta.powi(2) * (theta - 1.0);
+                (0..state0.len())
+                    .map(|i| h00 * state0[i] + h10 * h * derivative0[i] + h01 * state1[i] + h11 * h * derivative1[i])
+                    .collect()
+            })
+        }
+
+        /// Returns the approximate state at time t
+        pub fn evaluate(&self, t: f64) -> Vec<f64> {
+            (self.interpolant)(t)
+        }
+    }
+
+    /// Returns the times t_start, t_start + dt, ... up to and including t_end, independent of any solver step size
+    pub fn uniform_times(t_start: f64, t_end: f64, dt: f64) -> Vec<f64> {
+        let count: usize = ((t_end - t_start) / dt + 1e-9).floor() as usize;
+        (0..=count).map(|i| t_start + i as f64 * dt).collect()
+    }
+
+    /// Runs one of the fixed step `Integrator` schemes through the `OdeSolver` interface
+    #[derive(Debug, Clone)]
+    pub struct FixedStep {
+        pub integrator: Integrator,
+        pub dt: f64,
+        stats: SolverStats
+    }
+
+    impl FixedStep {
+        /// Creates a FixedStep solver
+        ///
+        /// *Params
+        ///
+        /// integrator: scheme used for each step
+        ///
+        /// dt: size of each step. The last step is shortened to land on the end time
+        pub fn new(integrator: Integrator, dt: f64) -> FixedStep {
+            FixedStep {
+                integrator,
+                dt,
+                stats: SolverStats::default()
+            }
+        }
+    }
+
+    impl OdeSolver for FixedStep {
+        fn step(&mut self, system: &dyn DynamicalSystem, t: f64, state: &[f64], t_end: f64) -> Result<(f64, Vec<f64>), SolverError> {
+            let (dt, t_next): (f64, f64) = if t_end - t <= self.dt * (1.0 + 1e-6) { (t_end - t, t_end) } else { (self.dt, t + self.dt) };
+            self.stats.accepted_steps += 1;
+            self.stats.function_evaluations += self.integrator.evaluations_per_step();
+            Ok((t_next, self.integrator.step(system, t, state, dt)))
+        }
+
+        fn stats(&self) -> SolverStats {
+            self.stats
+        }
+
+        fn reset(&mut self) {
+            self.stats = SolverStats::default();
+        }
+    }
+
+    impl Checkpointable for Integrator {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("Integrator");
+            checkpoint.put("scheme", format!("{:?}", self));
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Integrator, CheckpointError> {
+            checkpoint.expect_kind("Integrator")?;
+            match checkpoint.raw("scheme")? {
+                "Euler" => Ok(Integrator::Euler),
+                "RungeKutta4" => Ok(Integrator::RungeKutta4),
+                value => Err(CheckpointError::Invalid { key: "scheme".to_string(), value: value.to_string() })
+            }
+        }
+    }
+
+    impl Checkpointable for SolverStats {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("SolverStats");
+            checkpoint.put("accepted_steps", self.accepted_steps);
+            checkpoint.put("rejected_steps", self.rejected_steps);
+            checkpoint.put("function_evaluations", self.function_evaluations);
+            checkpoint.put("jacobian_evaluations", self.jacobian_evaluations);
+            checkpoint.put("newton_iterations", self.newton_iterations);
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<SolverStats, CheckpointError> {
+            checkpoint.expect_kind("SolverStats")?;
+            Ok(SolverStats {
+                accepted_steps: checkpoint.get("accepted_steps")?,
+                rejected_steps: checkpoint.get("rejected_steps")?,
+                function_evaluations: checkpoint.get("function_evaluations")?,
+                jacobian_evaluations: checkpoint.get("jacobian_evaluations")?,
+                newton_iterations: checkpoint.get("newton_iterations")?
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::adaptive::adaptive::DormandPrince;
+        use crate::dynamical_system::dynamical_system::test_systems::HarmonicOscillator;
+        use crate::stiff::stiff::Bdf;
+
+        /// Returns the largest error of a solution of the harmonic oscillator started from (1, 0)
+        fn solution_error(system: &HarmonicOscillator, solution: &Solution) -> f64 {
+            solution
+                .times
+                .iter()
+                .zip(solution.states.iter())
+                .flat_map(|(&t, state)| state.iter().zip(system.solution(t)).map(|(a, b)| (a - b).abs()).collect::<Vec<f64>>())
+                .fold(0.0, f64::max)
+        }
+
+        #[test]
+        fn dormand_prince_dense_output_matches_the_solution_between_steps() {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            let times: Vec<f64> = uniform_times(0.0, 10.0, 0.01);
+            let solution: Solution = DormandPrince::new(1e-8, 1e-8).solve_at(&system, 0.0, &[1.0, 0.0], &times).unwrap();
+            assert!(solution.stats.accepted_steps * 10 < times.len(), "the output times must fall between the steps");
+            assert!(solution_error(&system, &solution) < 1e-6, "interpolation error {}", solution_error(&system, &solution));
+        }
+
+        #[test]
+        fn bdf_dense_output_matches_the_solution_between_steps() {
+            // BDF2 is far less accurate than its tolerance per step suggests, so the interpolated values
+            // are compared with the error the solver already makes at its own steps
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            let steps: Solution = Bdf::new(1e-6, 1e-6).solve(&system, 0.0, &[1.0, 0.0], 10.0).unwrap();
+            let times: Vec<f64> = uniform_times(0.0, 10.0, 0.01);
+            let interpolated: Solution = Bdf::new(1e-6, 1e-6).solve_at(&system, 0.0, &[1.0, 0.0], &times).unwrap();
+            assert!(interpolated.stats.accepted_steps < times.len(), "the output times must fall between the steps");
+            assert!(
+                solution_error(&system, &interpolated) < 1.1 * solution_error(&system, &steps),
+                "interpolation error {} against {} at the steps",
+                solution_error(&system, &interpolated),
+                solution_error(&system, &steps)
+            );
+        }
+
+        #[test]
+        fn dense_output_of_a_single_step_is_accurate_inside_it() {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            let mut solver: DormandPrince = DormandPrince::new(1e-6, 1e-6).with_initial_step(0.5);
+            let (t1, state1): (f64, Vec<f64>) = solver.step(&system, 0.0, &[1.0, 0.0], 10.0).unwrap();
+            let dense: DenseOutput = solver.dense_output(&system, 0.0, &[1.0, 0.0], t1, &state1);
+            for k in 0..=10 {
+                let t: f64 = t1 * k as f64 / 10.0;
+                let error: f64 = dense.evaluate(t).iter().zip(system.solution(t)).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+                assert!(error < 1e-6, "interpolation error {} at t = {}", error, t);
+            }
+        }
+    }
+}
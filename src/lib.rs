@@ -0,0 +1,29 @@
+#![allow(clippy::module_inception)]
+
+pub mod dynamical_system;
+pub mod integrators;
+pub mod adaptive;
+pub mod symplectic;
+pub mod stiff;
+pub mod stochastic;
+pub mod delay;
+pub mod variational;
+pub mod lyapunov;
+pub mod fractal_dimension;
+pub mod autodiff;
+pub mod ensemble;
+pub mod seeding;
+pub mod checkpoint;
+pub mod linear_algebra;
+pub mod events;
+pub mod poincare;
+pub mod bifurcation;
+pub mod extrema;
+pub mod spectrum;
+pub mod trajectory;
+pub mod lorenz;
+pub mod double_pendulum;
+pub mod rossler;
+pub mod mackey_glass;
+pub mod cellular_automata;
+pub mod quantum_chaos;
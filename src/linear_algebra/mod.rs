@@ -0,0 +1,126 @@
+pub mod linear_algebra {
+    /// LU decomposition with partial pivoting of a square matrix, stored in place
+    #[derive(Debug, Clone)]
+    pub struct LuDecomposition {
+        lu: Vec<Vec<f64>>,
+        pivots: Vec<usize>
+    }
+
+    impl LuDecomposition {
+        /// Factorizes a square matrix given as rows. Returns None when the matrix is singular
+        pub fn new(mut matrix: Vec<Vec<f64>>) -> Option<LuDecomposition> {
+            let n: usize = matrix.len();
+            let mut pivots: Vec<usize> = (0..n).collect();
+
+            for k in 0..n {
+                let pivot_row: usize = (k..n)
+                    .max_by(|&a, &b| matrix[a][k].abs().total_cmp(&matrix[b][k].abs()))
+                    .unwrap_or(k);
+                if matrix[pivot_row][k] == 0.0 || !matrix[pivot_row][k].is_finite() {
+                    return None;
+                }
+                matrix.swap(k, pivot_row);
+                pivots.swap(k, pivot_row);
+
+                let (upper, lower): (&mut [Vec<f64>], &mut [Vec<f64>]) = matrix.split_at_mut(k + 1);
+                let pivot: &Vec<f64> = &upper[k];
+                for row in lower.iter_mut() {
+                    let factor: f64 = row[k] / pivot[k];
+                    row[k] = factor;
+                    for (value, pivot_value) in row[k + 1..].iter_mut().zip(pivot[k + 1..].iter()) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+
+            Some(LuDecomposition {
+                lu: matrix,
+                pivots
+            })
+        }
+
+        /// Solves A x = rhs for the factorized matrix A
+        pub fn solve(&self, rhs: &[f64]) -> Vec<f64> {
+            let n: usize = self.lu.len();
+            let mut x: Vec<f64> = self.pivots.iter().map(|&p| rhs[p]).collect();
+
+            for i in 0..n {
+                for j in 0..i {
+                    x[i] -= self.lu[i][j] * x[j];
+                }
+            }
+            for i in (0..n).rev() {
+                for j in i + 1..n {
+                    x[i] -= self.lu[i][j] * x[j];
+                }
+                x[i] /= self.lu[i][i];
+            }
+
+            x
+        }
+    }
+
+    /// Solves the square linear system A x = rhs. Returns None when A is singular
+    pub fn solve_linear_system(matrix: Vec<Vec<f64>>, rhs: &[f64]) -> Option<Vec<f64>> {
+        LuDecomposition::new(matrix).map(|lu| lu.solve(rhs))
+    }
+
+    /// Thin QR decomposition of the matrix whose columns are the given vectors, by modified Gram-Schmidt.
+    ///
+    /// Returns the orthonormal columns of Q, in the same order as the input, and the diagonal of R,
+    /// which holds the length of each vector once its components along the previous ones are removed.
+    /// Returns None when the vectors are not finite or linearly dependent to within rounding, that is when
+    /// removing the previous components leaves no more than rounding error of a vector
+    pub fn gram_schmidt(vectors: &[Vec<f64>]) -> Option<(Vec<Vec<f64>>, Vec<f64>)> {
+        let mut orthonormal: Vec<Vec<f64>> = vectors.to_vec();
+        let mut diagonal: Vec<f64> = Vec::with_capacity(vectors.len());
+
+        for j in 0..orthonormal.len() {
+            let (previous, rest): (&mut [Vec<f64>], &mut [Vec<f64>]) = orthonormal.split_at_mut(j);
+            let vector: &mut Vec<f64> = &mut rest[0];
+            let norm: f64 = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+            for q in previous.iter() {
+                let projection: f64 = q.iter().zip(vector.iter()).map(|(a, b)| a * b).sum();
+                for (value, q_value) in vector.iter_mut().zip(q.iter()) {
+                    *value -= projection * q_value;
+                }
+            }
+            let length: f64 = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+            if length <= f64::EPSILON * norm || !length.is_finite() {
+                return None;
+            }
+            for value in vector.iter_mut() {
+                *value /= length;
+            }
+            diagonal.push(length);
+        }
+
+        Some((orthonormal, diagonal))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn gram_schmidt_orthonormalizes_and_keeps_the_lengths() {
+            let (orthonormal, diagonal): (Vec<Vec<f64>>, Vec<f64>) = gram_schmidt(&[vec![3.0, 4.0, 0.0], vec![1.0, 0.0, 2.0]]).unwrap();
+            for (i, q) in orthonormal.iter().enumerate() {
+                for (j, p) in orthonormal.iter().enumerate() {
+                    let dot: f64 = q.iter().zip(p.iter()).map(|(a, b)| a * b).sum();
+                    assert!((dot - if i == j { 1.0 } else { 0.0 }).abs() < 1e-15);
+                }
+            }
+            assert_eq!(diagonal[0], 5.0);
+            assert!((diagonal[1] - (5.0 - 0.36_f64).sqrt()).abs() < 1e-15);
+        }
+
+        #[test]
+        fn dependent_vectors_are_rejected() {
+            assert_eq!(gram_schmidt(&[vec![1.0, 2.0], vec![2.0, 4.0]]), None);
+            assert_eq!(gram_schmidt(&[vec![0.1, 0.3, 0.7], vec![0.3, 0.9, 2.1]]), None);
+            assert_eq!(gram_schmidt(&[vec![0.0, 0.0]]), None);
+            assert_eq!(gram_schmidt(&[vec![f64::INFINITY, 0.0]]), None);
+        }
+    }
+}
@@ -1,5 +1,14 @@
 pub mod lorenz {
     use plotters::prelude::*;
+    use crate::autodiff::autodiff::{GenericVectorField, Scalar};
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::extrema::extrema::{self, Extremum, ReturnMap};
+    use crate::integrators::integrators::{FixedStep, Integrator};
+    use crate::poincare::poincare::PoincareSection;
+    use crate::stochastic::stochastic::{Noise, SdeIntegrator, SdeScheme};
+    use crate::trajectory::trajectory::{Sampling, Trajectory, TrajectoryStream};
+    #[derive(Debug, Clone)]
     pub struct LorenzSystem {
         pub x: f64,
         pub y: f64,
@@ -8,7 +17,9 @@ pub mod lorenz {
         pub rho: f64,
         pub beta: f64,
         pub dt: f64,
-        pub steps: usize
+        pub steps: usize,
+        pub integrator: Integrator,
+        pub sampling: Sampling
     }
 
     impl LorenzSystem {
@@ -35,6 +46,7 @@ pub mod lorenz {
         /// Typically set in a range from 0.001 to 0.1
         /// 
         /// steps: number of iterations in the simulation
+        #[allow(clippy::too_many_arguments)]
         pub fn new(x: f64, y: f64, z: f64, sigma: f64, rho: f64, beta: f64, dt: f64, steps: usize) -> LorenzSystem {
             LorenzSystem {
                 x,
@@ -44,31 +56,96 @@ pub mod lorenz {
                 rho,
                 beta,
                 dt,
-                steps
+                steps,
+                integrator: Integrator::Euler,
+                sampling: Sampling::default()
             }
         }
 
-        /// Returns the x, y, and z trajectories of the lorenz system
-        pub fn simulate(&mut self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
-            let mut x_values: Vec<f64> = Vec::with_capacity(self.steps);
-            let mut y_values: Vec<f64> = Vec::with_capacity(self.steps);
-            let mut z_values: Vec<f64> = Vec::with_capacity(self.steps);
-            
-            for _ in 0..self.steps {
-                x_values.push(self.x);
-                y_values.push(self.y);
-                z_values.push(self.z);
-                
-                let dx: f64 = self.sigma * (self.y - self.x);
-                let dy: f64 = self.x * (self.rho - self.z) - self.y;
-                let dz: f64 = self.x * self.y - self.beta * self.z;
-
-                self.x += self.dt * dx;
-                self.y += self.dt * dy;
-                self.z += self.dt * dz;
+        /// Sets the integration scheme used by `simulate`. Defaults to `Integrator::Euler`
+        pub fn with_integrator(mut self, integrator: Integrator) -> LorenzSystem {
+            self.integrator = integrator;
+            self
+        }
+
+        /// Discards the points of the simulation with times before the given duration. The discarded steps still count towards `steps`
+        pub fn with_transient(mut self, duration: f64) -> LorenzSystem {
+            self.sampling = self.sampling.with_transient(duration);
+            self
+        }
+
+        /// Keeps only every stride-th point of the simulation
+        pub fn with_stride(mut self, stride: usize) -> LorenzSystem {
+            self.sampling = self.sampling.with_stride(stride);
+            self
+        }
+
+        /// Returns a lazy stream over the points of the lorenz system, integrated with `self.integrator` for `self.steps` steps
+        pub fn stream(&self) -> TrajectoryStream<LorenzSystem> {
+            TrajectoryStream::new(self.clone(), self.integrator, self.dt)
+                .with_steps(self.steps)
+                .with_sampling(self.sampling)
+        }
+
+        /// Returns the trajectory of the lorenz system, integrated with `self.integrator`
+        pub fn simulate_trajectory(&mut self) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(self);
+
+            for i in 0..self.steps {
+                let t: f64 = i as f64 * self.dt;
+                let state: [f64; 3] = [self.x, self.y, self.z];
+                if self.sampling.keeps(i, self.dt) {
+                    trajectory.push(t, &state);
+                }
+
+                let next: Vec<f64> = self.integrator.step(self, t, &state, self.dt);
+
+                self.x = next[0];
+                self.y = next[1];
+                self.z = next[2];
             }
 
-            (x_values, y_values, z_values)
+            trajectory
+        }
+
+        /// Returns the x, y, and z trajectories of the lorenz system, integrated with `self.integrator`
+        pub fn simulate(&mut self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+            self.simulate_trajectory().into()
+        }
+
+        /// Returns the trajectory of the lorenz system driven by noise, for `self.steps` steps of size `self.dt` from the initial state
+        ///
+        /// *Params
+        ///
+        /// noise: intensity of the noise on each of x, y and z, either additive or multiplicative
+        ///
+        /// scheme: stochastic integration scheme
+        ///
+        /// seed: seed of the noise. Equal seeds produce identical trajectories
+        pub fn simulate_sde(&self, noise: Noise, scheme: SdeScheme, seed: u64) -> Trajectory {
+            SdeIntegrator::new(self, noise, scheme, self.dt, seed).simulate(self.steps, self.sampling)
+        }
+
+        /// Returns the points where the lorenz system crosses a Poincare section, integrated with `self.integrator`
+        /// for `self.steps` steps of size `self.dt` from the initial state. The crossings are located between
+        /// the steps, so they lie on the section plane
+        pub fn poincare_section(&self, section: &PoincareSection) -> Trajectory {
+            let mut solver: FixedStep = FixedStep::new(self.integrator, self.dt);
+            section
+                .compute(&mut solver, self, 0.0, &self.initial_state(), self.steps as f64 * self.dt)
+                .expect("fixed step integration cannot fail")
+        }
+
+        /// Returns the local extrema of one state variable (0 for x, 1 for y, 2 for z) over the points of
+        /// `stream()`, with each peak interpolated between the steps
+        pub fn extrema(&self, variable: usize) -> Vec<Extremum> {
+            extrema::trajectory_extrema(&self.stream().collect_trajectory(), variable)
+        }
+
+        /// Returns the map of successive maxima of one state variable over the points of `stream()`.
+        /// For z this is the map z_n -> z_(n+1) of Lorenz's 1963 paper
+        pub fn return_map(&self, variable: usize) -> ReturnMap {
+            ReturnMap::next_maximum(&self.stream().collect_trajectory(), variable)
         }
 
         /// Plots the Lorenz system trajectories
@@ -81,7 +158,7 @@ pub mod lorenz {
                 .margin(5)
                 .x_label_area_size(40)
                 .y_label_area_size(40)
-                .build_cartesian_2d(0.0..self.steps as f64, -20.0..20.0)
+                .build_cartesian_2d(self.sampling.transient_steps(self.dt) as f64..self.steps as f64, -20.0..20.0)
                 .unwrap();
 
             chart
@@ -92,24 +169,98 @@ pub mod lorenz {
                 .unwrap();
 
             chart
-                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (i as f64, val)), &RED))
+                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &RED))
                 .unwrap()
                 .label("X Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
             chart
-                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (i as f64, val)), &GREEN))
+                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &GREEN))
                 .unwrap()
                 .label("Y Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], &GREEN));
+                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], GREEN));
 
             chart
-                .draw_series(LineSeries::new(trajectories.2.iter().enumerate().map(|(i, &val)| (i as f64, val)), &BLUE))
+                .draw_series(LineSeries::new(trajectories.2.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &BLUE))
                 .unwrap()
                 .label("Z Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y - 20), (x + 20, y - 20)], &BLUE));
+                .legend(|(x, y)| PathElement::new(vec![(x, y - 20), (x + 20, y - 20)], BLUE));
+
+            chart.configure_series_labels().background_style(WHITE.mix(0.8)).draw().unwrap();
+        }
+    }
+
+    impl DynamicalSystem for LorenzSystem {
+        fn dimension(&self) -> usize {
+            3
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            vec![("sigma", self.sigma), ("rho", self.rho), ("beta", self.beta)]
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            vec!["x".to_string(), "y".to_string(), "z".to_string()]
+        }
+
+        fn initial_state(&self) -> Vec<f64> {
+            vec![self.x, self.y, self.z]
+        }
+
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+            self.vector_field(t, state)
+        }
+
+        fn jacobian(&self, _t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            vec![
+                vec![-self.sigma, self.sigma, 0.0],
+                vec![self.rho - state[2], -1.0, -state[0]],
+                vec![state[1], state[0], -self.beta]
+            ]
+        }
+    }
+
+    impl GenericVectorField for LorenzSystem {
+        fn vector_field<T: Scalar>(&self, _t: f64, state: &[T]) -> Vec<T> {
+            let dx: T = (state[1] - state[0]) * self.sigma;
+            let dy: T = state[0] * (T::constant(self.rho) - state[2]) - state[1];
+            let dz: T = state[0] * state[1] - state[2] * self.beta;
+            vec![dx, dy, dz]
+        }
+    }
+
+    /// Saves the parameters and the current state. `simulate_trajectory` restarts its step count on every
+    /// call, so to resume a run part way through checkpoint the `stream()` instead
+    impl Checkpointable for LorenzSystem {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("LorenzSystem");
+            checkpoint.put("x", self.x);
+            checkpoint.put("y", self.y);
+            checkpoint.put("z", self.z);
+            checkpoint.put("sigma", self.sigma);
+            checkpoint.put("rho", self.rho);
+            checkpoint.put("beta", self.beta);
+            checkpoint.put("dt", self.dt);
+            checkpoint.put("steps", self.steps);
+            checkpoint.put_section("integrator", &self.integrator.to_checkpoint());
+            checkpoint.put_section("sampling", &self.sampling.to_checkpoint());
+            checkpoint
+        }
 
-            chart.configure_series_labels().background_style(&WHITE.mix(0.8)).draw().unwrap();
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<LorenzSystem, CheckpointError> {
+            checkpoint.expect_kind("LorenzSystem")?;
+            Ok(LorenzSystem {
+                x: checkpoint.get("x")?,
+                y: checkpoint.get("y")?,
+                z: checkpoint.get("z")?,
+                sigma: checkpoint.get("sigma")?,
+                rho: checkpoint.get("rho")?,
+                beta: checkpoint.get("beta")?,
+                dt: checkpoint.get("dt")?,
+                steps: checkpoint.get("steps")?,
+                integrator: Integrator::from_checkpoint(&checkpoint.section("integrator"))?,
+                sampling: Sampling::from_checkpoint(&checkpoint.section("sampling"))?
+            })
         }
     }
-}
\ No newline at end of file
+}
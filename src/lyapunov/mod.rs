@@ -0,0 +1,323 @@
+pub mod lyapunov {
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::Integrator;
+    use crate::linear_algebra::linear_algebra::gram_schmidt;
+    use crate::variational::variational::{TangentFlow, VariationalSystem};
+
+    /// Result of a largest Lyapunov exponent estimation
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct LyapunovEstimate {
+        /// Final estimate of the exponent
+        pub exponent: f64,
+        /// Times of the renormalizations after the transient
+        pub times: Vec<f64>,
+        /// Running estimate of the exponent after each renormalization, for judging convergence
+        pub history: Vec<f64>
+    }
+
+    /// Benettin estimator of the largest Lyapunov exponent of a flow.
+    ///
+    /// A single tangent vector is integrated along the trajectory with the variational equations and
+    /// renormalized at a fixed interval. The exponent is the average logarithmic growth of its length,
+    /// accumulated only once the transient has passed
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct LargestLyapunov {
+        pub integrator: Integrator,
+        pub dt: f64,
+        pub duration: f64,
+        pub renormalization_interval: f64,
+        pub transient: f64
+    }
+
+    impl LargestLyapunov {
+        /// Creates a LargestLyapunov estimator using RK4, renormalizing every step and with no transient
+        ///
+        /// *Params
+        ///
+        /// dt: size of each integration step
+        ///
+        /// duration: time over which the growth is averaged, not counting the transient
+        pub fn new(dt: f64, duration: f64) -> LargestLyapunov {
+            assert!(dt > 0.0 && duration > 0.0, "dt and duration must be positive");
+            LargestLyapunov {
+                integrator: Integrator::RungeKutta4,
+                dt,
+                duration,
+                renormalization_interval: dt,
+                transient: 0.0
+            }
+        }
+
+        /// Sets the integration scheme. Defaults to `Integrator::RungeKutta4`
+        pub fn with_integrator(mut self, integrator: Integrator) -> LargestLyapunov {
+            self.integrator = integrator;
+            self
+        }
+
+        /// Sets the time between renormalizations of the tangent vector, rounded to a whole number of steps
+        pub fn with_renormalization_interval(mut self, interval: f64) -> LargestLyapunov {
+            assert!(interval > 0.0, "renormalization interval must be positive");
+            self.renormalization_interval = interval;
+            self
+        }
+
+        /// Sets the time integrated before the growth starts being accumulated, so the trajectory can
+        /// settle onto the attractor and the tangent vector can align with the most unstable direction
+        pub fn with_transient(mut self, transient: f64) -> LargestLyapunov {
+            assert!(transient >= 0.0, "transient must not be negative");
+            self.transient = transient;
+            self
+        }
+
+        /// Returns the number of steps between renormalizations
+        pub fn renormalization_steps(&self) -> usize {
+            ((self.renormalization_interval / self.dt).round() as usize).max(1)
+        }
+
+        /// Estimates the largest Lyapunov exponent of a system from its initial state.
+        ///
+        /// Returns None when the tangent vector collapses or overflows between two renormalizations,
+        /// in which case a shorter renormalization interval is needed
+        pub fn estimate<S: DynamicalSystem>(&self, system: S) -> Option<LyapunovEstimate> {
+            let n: usize = system.dimension();
+            let initial_vector: Vec<f64> = vec![1.0 / (n as f64).sqrt(); n];
+            let variational: VariationalSystem<S> = VariationalSystem::new(system).with_tangent_vectors(vec![initial_vector]);
+            let mut flow: TangentFlow<S> = TangentFlow::new(variational, self.integrator, self.dt);
+
+            let steps_per_renormalization: usize = self.renormalization_steps();
+            let interval: f64 = steps_per_renormalization as f64 * self.dt;
+            let transient_intervals: usize = (self.transient / interval - 1e-9).ceil().max(0.0) as usize;
+            let intervals: usize = ((self.duration / interval - 1e-9).ceil() as usize).max(1);
+
+            let mut log_growth: f64 = 0.0;
+            let mut times: Vec<f64> = Vec::with_capacity(intervals);
+            let mut history: Vec<f64> = Vec::with_capacity(intervals);
+
+            for interval_index in 0..transient_intervals + intervals {
+                for _ in 0..steps_per_renormalization {
+                    flow.step();
+                }
+
+                let vector: Vec<f64> = flow.tangent_vectors().remove(0);
+                let length: f64 = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+                if length == 0.0 || !length.is_finite() {
+                    return None;
+                }
+                flow.set_tangent_vectors(&[vector.iter().map(|v| v / length).collect()]);
+
+                if interval_index >= transient_intervals {
+                    log_growth += length.ln();
+                    let elapsed: f64 = (interval_index + 1 - transient_intervals) as f64 * interval;
+                    times.push(flow.time());
+                    history.push(log_growth / elapsed);
+                }
+            }
+
+            Some(LyapunovEstimate {
+                exponent: *history.last().unwrap(),
+                times,
+                history
+            })
+        }
+    }
+
+    /// Result of a Lyapunov spectrum estimation
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SpectrumEstimate {
+        /// Final estimates of the exponents, in decreasing order
+        pub exponents: Vec<f64>,
+        /// Times of the re-orthonormalizations after the transient
+        pub times: Vec<f64>,
+        /// Running estimates of all exponents after each re-orthonormalization
+        pub history: Vec<Vec<f64>>
+    }
+
+    impl SpectrumEstimate {
+        /// Returns the Kaplan-Yorke dimension of the estimated spectrum
+        pub fn kaplan_yorke_dimension(&self) -> f64 {
+            kaplan_yorke_dimension(&self.exponents)
+        }
+    }
+
+    /// Estimator of the Lyapunov spectrum of a flow.
+    ///
+    /// A set of tangent vectors is integrated along the trajectory with the variational equations and
+    /// re-orthonormalized at a fixed interval with a QR decomposition. The exponents are the average
+    /// logarithmic growths of the diagonal of R, accumulated only once the transient has passed
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct LyapunovSpectrum {
+        pub integrator: Integrator,
+        pub dt: f64,
+        pub duration: f64,
+        pub renormalization_interval: f64,
+        pub transient: f64,
+        /// Number of exponents estimated. When unset the whole spectrum is estimated
+        pub count: Option<usize>
+    }
+
+    impl LyapunovSpectrum {
+        /// Creates a LyapunovSpectrum estimator for the whole spectrum using RK4, re-orthonormalizing
+        /// every step and with no transient
+        ///
+        /// *Params
+        ///
+        /// dt: size of each integration step
+        ///
+        /// duration: time over which the growths are averaged, not counting the transient
+        pub fn new(dt: f64, duration: f64) -> LyapunovSpectrum {
+            assert!(dt > 0.0 && duration > 0.0, "dt and duration must be positive");
+            LyapunovSpectrum {
+                integrator: Integrator::RungeKutta4,
+                dt,
+                duration,
+                renormalization_interval: dt,
+                transient: 0.0,
+                count: None
+            }
+        }
+
+        /// Sets the integration scheme. Defaults to `Integrator::RungeKutta4`
+        pub fn with_integrator(mut self, integrator: Integrator) -> LyapunovSpectrum {
+            self.integrator = integrator;
+            self
+        }
+
+        /// Sets the time between re-orthonormalizations, rounded to a whole number of steps
+        pub fn with_renormalization_interval(mut self, interval: f64) -> LyapunovSpectrum {
+            assert!(interval > 0.0, "renormalization interval must be positive");
+            self.renormalization_interval = interval;
+            self
+        }
+
+        /// Sets the time integrated before the growths start being accumulated
+        pub fn with_transient(mut self, transient: f64) -> LyapunovSpectrum {
+            assert!(transient >= 0.0, "transient must not be negative");
+            self.transient = transient;
+            self
+        }
+
+        /// Estimates only the given number of largest exponents
+        pub fn with_count(mut self, count: usize) -> LyapunovSpectrum {
+            assert!(count > 0, "at least one exponent must be estimated");
+            self.count = Some(count);
+            self
+        }
+
+        /// Returns the number of steps between re-orthonormalizations
+        pub fn renormalization_steps(&self) -> usize {
+            ((self.renormalization_interval / self.dt).round() as usize).max(1)
+        }
+
+        /// Estimates the Lyapunov spectrum of a system from its initial state.
+        ///
+        /// Returns None when the tangent vectors become linearly dependent or overflow between two
+        /// re-orthonormalizations, in which case a shorter renormalization interval is needed
+        pub fn estimate<S: DynamicalSystem>(&self, system: S) -> Option<SpectrumEstimate> {
+            let n: usize = system.dimension();
+            let count: usize = self.count.unwrap_or(n);
+            assert!(count <= n, "cannot estimate more exponents than the system dimension");
+            let variational: VariationalSystem<S> = VariationalSystem::new(system);
+            let initial_vectors: Vec<Vec<f64>> = (0..count)
+                .map(|j| (0..n).map(|i| if i == j { 1.0 } else { 0.0 }).collect())
+                .collect();
+            let mut flow: TangentFlow<S> = TangentFlow::new(variational.with_tangent_vectors(initial_vectors), self.integrator, self.dt);
+
+            let steps_per_renormalization: usize = self.renormalization_steps();
+            let interval: f64 = steps_per_renormalization as f64 * self.dt;
+            let transient_intervals: usize = (self.transient / interval - 1e-9).ceil().max(0.0) as usize;
+            let intervals: usize = ((self.duration / interval - 1e-9).ceil() as usize).max(1);
+
+            let mut log_growths: Vec<f64> = vec![0.0; count];
+            let mut times: Vec<f64> = Vec::with_capacity(intervals);
+            let mut history: Vec<Vec<f64>> = Vec::with_capacity(intervals);
+
+            for interval_index in 0..transient_intervals + intervals {
+                for _ in 0..steps_per_renormalization {
+                    flow.step();
+                }
+
+                let (orthonormal, lengths): (Vec<Vec<f64>>, Vec<f64>) = gram_schmidt(&flow.tangent_vectors())?;
+                flow.set_tangent_vectors(&orthonormal);
+
+                if interval_index >= transient_intervals {
+                    let elapsed: f64 = (interval_index + 1 - transient_intervals) as f64 * interval;
+                    for (log_growth, length) in log_growths.iter_mut().zip(lengths.iter()) {
+                        *log_growth += length.ln();
+                    }
+                    times.push(flow.time());
+                    history.push(log_growths.iter().map(|log_growth| log_growth / elapsed).collect());
+                }
+            }
+
+            Some(SpectrumEstimate {
+                exponents: history.last().unwrap().clone(),
+                times,
+                history
+            })
+        }
+    }
+
+    /// Returns the Kaplan-Yorke (Lyapunov) dimension of a spectrum: j + (l_1 + ... + l_j) / |l_(j+1)|,
+    /// where j is the largest number of exponents, taken in decreasing order, whose sum is not negative
+    pub fn kaplan_yorke_dimension(exponents: &[f64]) -> f64 {
+        let mut sorted: Vec<f64> = exponents.to_vec();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+
+        let mut sum: f64 = 0.0;
+        for (j, exponent) in sorted.iter().enumerate() {
+            if sum + exponent < 0.0 {
+                return j as f64 + sum / exponent.abs();
+            }
+            sum += exponent;
+        }
+        sorted.len() as f64
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::double_pendulum::double_pendulum::DoublePendulum;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        fn lorenz() -> LorenzSystem {
+            LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 0)
+        }
+
+        #[test]
+        fn largest_exponent_of_lorenz_matches_the_literature() {
+            let estimate: LyapunovEstimate = LargestLyapunov::new(0.01, 1000.0).with_transient(50.0).estimate(lorenz()).unwrap();
+            assert!((estimate.exponent - 0.906).abs() < 0.02, "{}", estimate.exponent);
+        }
+
+        #[test]
+        fn lorenz_spectrum_sums_to_the_divergence() {
+            let estimate: SpectrumEstimate = LyapunovSpectrum::new(0.01, 1000.0).with_transient(50.0).estimate(lorenz()).unwrap();
+            let sum: f64 = estimate.exponents.iter().sum();
+            assert!((sum + 10.0 + 1.0 + 8.0 / 3.0).abs() < 0.01, "{:?}", estimate.exponents);
+            assert!((estimate.exponents[0] - 0.906).abs() < 0.02, "{:?}", estimate.exponents);
+            assert!(estimate.exponents[1].abs() < 0.02, "{:?}", estimate.exponents);
+            assert!((estimate.kaplan_yorke_dimension() - 2.06).abs() < 0.01, "{}", estimate.kaplan_yorke_dimension());
+        }
+
+        #[test]
+        fn double_pendulum_exponents_come_in_opposite_pairs() {
+            for (theta1, theta2) in [(1.0, 2.0), (2.0, 2.5)] {
+                let pendulum: DoublePendulum = DoublePendulum::new(theta1, theta2, 0.0, 0.0, 0.01, 9.81, 0);
+                let exponents: Vec<f64> = LyapunovSpectrum::new(0.01, 200.0).with_transient(20.0).estimate(pendulum).unwrap().exponents;
+                assert!(exponents[0] > 0.3, "{:?}", exponents);
+                assert!((exponents[0] + exponents[3]).abs() < 0.02, "{:?}", exponents);
+                assert!((exponents[1] + exponents[2]).abs() < 0.02, "{:?}", exponents);
+                assert!(exponents.iter().sum::<f64>().abs() < 0.005, "{:?}", exponents);
+            }
+        }
+
+        #[test]
+        fn overflowing_tangent_vectors_are_reported() {
+            let spectrum: LyapunovSpectrum = LyapunovSpectrum::new(0.01, 1000.0).with_renormalization_interval(1000.0);
+            assert_eq!(spectrum.estimate(lorenz()), None);
+            let largest: LargestLyapunov = LargestLyapunov::new(0.01, 1000.0).with_renormalization_interval(1000.0);
+            assert_eq!(largest.estimate(lorenz()), None);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+pub mod mackey_glass {
+    use plotters::prelude::*;
+    use crate::delay::delay::{DdeIntegrator, DelaySystem, History};
+    use crate::trajectory::trajectory::{Sampling, Trajectory};
+
+    #[derive(Debug, Clone)]
+    pub struct MackeyGlass {
+        pub x: f64,
+        pub beta: f64,
+        pub gamma: f64,
+        pub n: f64,
+        pub tau: f64,
+        pub dt: f64,
+        pub steps: usize,
+        pub history: Option<History>,
+        pub sampling: Sampling
+    }
+
+    impl MackeyGlass {
+        /// Creates a MackeyGlass object, the delay system dx/dt = beta * x(t - tau) / (1 + x(t - tau)^n) - gamma * x
+        ///
+        /// *Params
+        ///
+        /// x: value of x at every time up to the start of the simulation
+        ///
+        /// beta: production rate
+        /// Typically set to 0.2
+        ///
+        /// gamma: decay rate
+        /// Typically set to 0.1
+        ///
+        /// n: steepness of the feedback nonlinearity
+        /// Typically set to 10
+        ///
+        /// tau: delay. The system is periodic for small delays and chaotic above about 16.8
+        /// Typically set to 17
+        ///
+        /// dt: small time increment used in each iteration of the simulation. Must not exceed tau
+        ///
+        /// steps: number of iterations in the simulation
+        pub fn new(x: f64, beta: f64, gamma: f64, n: f64, tau: f64, dt: f64, steps: usize) -> MackeyGlass {
+            MackeyGlass {
+                x,
+                beta,
+                gamma,
+                n,
+                tau,
+                dt,
+                steps,
+                history: None,
+                sampling: Sampling::default()
+            }
+        }
+
+        /// Replaces the constant initial history x with a function of time
+        pub fn with_history(mut self, history: History) -> MackeyGlass {
+            self.history = Some(history);
+            self
+        }
+
+        /// Discards the points of the simulation with times before the given duration. The discarded steps still count towards `steps`
+        pub fn with_transient(mut self, duration: f64) -> MackeyGlass {
+            self.sampling = self.sampling.with_transient(duration);
+            self
+        }
+
+        /// Keeps only every stride-th point of the simulation
+        pub fn with_stride(mut self, stride: usize) -> MackeyGlass {
+            self.sampling = self.sampling.with_stride(stride);
+            self
+        }
+
+        /// Returns the trajectory of the Mackey-Glass system, integrated with fourth order Runge-Kutta
+        pub fn simulate_trajectory(&self) -> Trajectory {
+            let history: History = self.history.clone().unwrap_or_else(|| History::Constant(vec![self.x]));
+            DdeIntegrator::new(self, history, self.dt).simulate(self.steps, self.sampling)
+        }
+
+        /// Returns the x values of the Mackey-Glass system
+        pub fn simulate(&self) -> Vec<f64> {
+            self.simulate_trajectory().columns.swap_remove(0)
+        }
+
+        /// Plots the Mackey-Glass trajectory
+        pub fn display(&self, trajectory: Vec<f64>) {
+            let root: DrawingArea<BitMapBackend<'_>, plotters::coord::Shift> = BitMapBackend::new("mackey_glass_plot.png", (800, 600)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+
+            let mut chart = ChartBuilder::on(&root)
+                .caption("Mackey-Glass Trajectory", ("Arial", 20).into_font())
+                .margin(5)
+                .x_label_area_size(40)
+                .y_label_area_size(40)
+                .build_cartesian_2d(self.sampling.transient_steps(self.dt) as f64..self.steps as f64, 0.0..2.0)
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .x_desc("Time Step")
+                .y_desc("Values")
+                .draw()
+                .unwrap();
+
+            chart
+                .draw_series(LineSeries::new(trajectory.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &RED))
+                .unwrap()
+                .label("X Trajectory")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+            chart.configure_series_labels().background_style(WHITE.mix(0.8)).draw().unwrap();
+        }
+    }
+
+    impl DelaySystem for MackeyGlass {
+        fn dimension(&self) -> usize {
+            1
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            vec![("beta", self.beta), ("gamma", self.gamma), ("n", self.n), ("tau", self.tau)]
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            vec!["x".to_string()]
+        }
+
+        fn delay(&self) -> f64 {
+            self.tau
+        }
+
+        fn derivative(&self, _t: f64, state: &[f64], delayed: &[f64]) -> Vec<f64> {
+            vec![self.beta * delayed[0] / (1.0 + delayed[0].powf(self.n)) - self.gamma * state[0]]
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Returns the largest difference between a trajectory and a reference sampled every 0.01
+        fn max_error(trajectory: &Trajectory, reference: &[f64]) -> f64 {
+            trajectory
+                .times
+                .iter()
+                .zip(trajectory.column(0).iter())
+                .map(|(t, x)| (x - reference[(t / 0.01).round() as usize]).abs())
+                .fold(0.0, f64::max)
+        }
+
+        #[test]
+        fn chaotic_solution_stays_bounded_and_positive() {
+            let x: Vec<f64> = MackeyGlass::new(0.5, 0.2, 0.1, 10.0, 17.0, 0.1, 20000).simulate();
+            assert_eq!(x.len(), 20000);
+            assert!(x.iter().all(|&value| value > 0.0 && value < 1.5), "{:?}", x.iter().cloned().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value))));
+        }
+
+        #[test]
+        fn history_is_interpolated_between_steps() {
+            // tau / dt is not a whole number for either coarse step, so every delayed state falls between buffered points
+            let reference: Vec<f64> = MackeyGlass::new(0.5, 0.2, 0.1, 10.0, 17.0, 0.01, 20001).simulate();
+            let coarse: f64 = max_error(&MackeyGlass::new(0.5, 0.2, 0.1, 10.0, 17.0, 0.3, 667).simulate_trajectory(), &reference);
+            let fine: f64 = max_error(&MackeyGlass::new(0.5, 0.2, 0.1, 10.0, 17.0, 0.15, 1334).simulate_trajectory(), &reference);
+            assert!(coarse < 1e-5, "{}", coarse);
+            assert!(fine < coarse / 4.0, "{} against {}", fine, coarse);
+        }
+    }
+}
@@ -1,14 +1,8 @@
-mod lorenz; 
-mod double_pendulum;
-mod rossler;
-mod cellular_automata;
-mod quantum_chaos;
-
-use lorenz::lorenz::LorenzSystem;
-use double_pendulum::double_pendulum::DoublePendulum;
-use rossler::rossler::RosslerSystem;
-use cellular_automata::cellular_automata::Grid;
-use quantum_chaos::random_matrix_theory::Matrix;
+use chaos_theory::lorenz::lorenz::LorenzSystem;
+//use chaos_theory::double_pendulum::double_pendulum::DoublePendulum;
+use chaos_theory::rossler::rossler::RosslerSystem;
+use chaos_theory::cellular_automata::cellular_automata::Grid;
+use chaos_theory::quantum_chaos::random_matrix_theory::Matrix;
 
 fn main() {
     let beta: f64 = 8.0 / 3.0;
@@ -42,7 +36,7 @@ fn main() {
     let rs_trajectories: (Vec<f64>, Vec<f64>, Vec<f64>) = rs.simulate();
     rs.display(rs_trajectories);
 
-    let mut g: Grid = Grid::new(10, 10, 100);
+    let _g: Grid = Grid::new(10, 10, 100);
     //g.simulate(1000);
 
     let mut goe_matrix: Matrix = Matrix::new(3, 3);
@@ -0,0 +1,171 @@
+pub mod poincare {
+    use std::sync::Arc;
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::events::events::{Direction, Event, EventRecord};
+    use crate::integrators::integrators::{OdeSolver, SolverError};
+    use crate::trajectory::trajectory::Trajectory;
+
+    /// Condition a crossing must satisfy to be kept in a section
+    pub type SectionCondition = Arc<dyn Fn(&[f64]) -> bool + Send + Sync>;
+
+    /// Hyperplane normal . x = offset in the state space of a system
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Hyperplane {
+        pub normal: Vec<f64>,
+        pub offset: f64
+    }
+
+    impl Hyperplane {
+        /// Creates a Hyperplane
+        ///
+        /// *Params
+        ///
+        /// normal: normal vector, one component per state variable. Crossings along it are rising
+        ///
+        /// offset: value of normal . x on the plane
+        pub fn new(normal: Vec<f64>, offset: f64) -> Hyperplane {
+            assert!(normal.iter().any(|&component| component != 0.0), "normal must not be zero");
+            Hyperplane { normal, offset }
+        }
+
+        /// Creates the Hyperplane x_index = value in a state space of the given dimension
+        pub fn coordinate(dimension: usize, index: usize, value: f64) -> Hyperplane {
+            assert!(index < dimension, "index is outside the state space");
+            let mut normal: Vec<f64> = vec![0.0; dimension];
+            normal[index] = 1.0;
+            Hyperplane::new(normal, value)
+        }
+
+        /// Returns normal . x - offset, which is positive on the side the normal points to
+        pub fn side(&self, state: &[f64]) -> f64 {
+            self.normal.iter().zip(state.iter()).map(|(n, x)| n * x).sum::<f64>() - self.offset
+        }
+    }
+
+    /// Poincare section of a flow: the points where its trajectory crosses a hyperplane in a given
+    /// direction, optionally restricted to the part of the plane where a condition holds.
+    ///
+    /// Crossings are located with the event machinery of the solvers, so the points lie on the plane
+    /// to the accuracy of the solver's dense output rather than at the nearest integration step
+    #[derive(Clone)]
+    pub struct PoincareSection {
+        pub plane: Hyperplane,
+        pub direction: Direction,
+        /// Crossings before this time are discarded
+        pub transient: f64,
+        condition: Option<SectionCondition>
+    }
+
+    impl PoincareSection {
+        /// Creates a PoincareSection keeping crossings of the plane in the direction of its normal
+        pub fn new(plane: Hyperplane) -> PoincareSection {
+            PoincareSection {
+                plane,
+                direction: Direction::Rising,
+                transient: 0.0,
+                condition: None
+            }
+        }
+
+        /// Sets the crossing direction. `Direction::Rising` follows the normal of the plane
+        pub fn with_direction(mut self, direction: Direction) -> PoincareSection {
+            self.direction = direction;
+            self
+        }
+
+        /// Keeps only the crossings whose state satisfies the condition, such as y < 0 for the x = 0 plane
+        pub fn with_condition<F: Fn(&[f64]) -> bool + Send + Sync + 'static>(mut self, condition: F) -> PoincareSection {
+            self.condition = Some(Arc::new(condition));
+            self
+        }
+
+        /// Discards the crossings before the given time, while the trajectory settles onto the attractor
+        pub fn with_transient(mut self, transient: f64) -> PoincareSection {
+            assert!(transient >= 0.0, "transient must not be negative");
+            self.transient = transient;
+            self
+        }
+
+        /// Returns whether a located crossing belongs to the section
+        pub fn keeps(&self, t: f64, state: &[f64]) -> bool {
+            t >= self.transient && self.condition.as_ref().is_none_or(|condition| condition(state))
+        }
+
+        /// Integrates a system and returns the section points together with the times they were reached
+        ///
+        /// *Params
+        ///
+        /// solver: solver used for the integration and for locating the crossings
+        ///
+        /// system: system to integrate
+        ///
+        /// t0, state0: initial time and state
+        ///
+        /// t_end: end of the integration
+        pub fn compute<O: OdeSolver + ?Sized>(&self, solver: &mut O, system: &dyn DynamicalSystem, t0: f64, state0: &[f64], t_end: f64) -> Result<Trajectory, SolverError> {
+            assert_eq!(self.plane.normal.len(), system.dimension(), "plane does not match the system dimension");
+            let plane: Hyperplane = self.plane.clone();
+            let event: Event = Event::new(move |_, state| plane.side(state)).with_direction(self.direction);
+            let records: Vec<EventRecord> = solver.locate_events(system, t0, state0, t_end, &[event])?;
+
+            let mut section: Trajectory = Trajectory::for_system(system);
+            for record in records.iter().filter(|record| self.keeps(record.t, &record.state)) {
+                section.push(record.t, &record.state);
+            }
+            Ok(section)
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::f64::consts::PI;
+        use crate::adaptive::adaptive::DormandPrince;
+        use crate::dynamical_system::dynamical_system::test_systems::HarmonicOscillator;
+
+        /// Returns the section of the oscillator (cos t, -sin t) over ten periods
+        fn section(section: &PoincareSection) -> Trajectory {
+            let system: HarmonicOscillator = HarmonicOscillator { omega: 1.0 };
+            section.compute(&mut DormandPrince::new(1e-10, 1e-10), &system, 0.0, &[1.0, 0.0], 20.0 * PI + 0.5).unwrap()
+        }
+
+        #[test]
+        fn section_points_lie_on_the_plane() {
+            let plane: Hyperplane = Hyperplane::new(vec![1.0, 1.0], -0.5);
+            let points: Trajectory = section(&PoincareSection::new(plane.clone()).with_direction(Direction::Either));
+            assert_eq!(points.len(), 20);
+            for i in 0..points.len() {
+                assert!(plane.side(&points.point(i)).abs() < 1e-9, "{:?}", points.point(i));
+            }
+        }
+
+        #[test]
+        fn only_crossings_in_the_direction_are_kept() {
+            let plane: Hyperplane = Hyperplane::coordinate(2, 1, 0.0);
+            let rising: Trajectory = section(&PoincareSection::new(plane.clone()));
+            let falling: Trajectory = section(&PoincareSection::new(plane).with_direction(Direction::Falling));
+
+            // y = -sin t rises through zero at odd multiples of pi, where x = -1, and falls at even ones
+            assert_eq!(rising.len(), 10);
+            assert_eq!(falling.len(), 10);
+            for k in 0..10 {
+                assert!((rising.times[k] - (2 * k + 1) as f64 * PI).abs() < 1e-8, "{:?}", rising.times);
+                assert!((falling.times[k] - (2 * k + 2) as f64 * PI).abs() < 1e-8, "{:?}", falling.times);
+                assert!((rising.point(k)[0] + 1.0).abs() < 1e-8 && (falling.point(k)[0] - 1.0).abs() < 1e-8);
+            }
+        }
+
+        #[test]
+        fn condition_and_transient_restrict_the_crossings() {
+            let plane: Hyperplane = Hyperplane::coordinate(2, 1, 0.0);
+            let positive: Trajectory = section(&PoincareSection::new(plane.clone()).with_direction(Direction::Either).with_condition(|state| state[0] > 0.0));
+            assert_eq!(positive.len(), 10);
+            assert!(positive.column(0).iter().all(|&x| x > 0.0));
+
+            let settled: Trajectory = section(&PoincareSection::new(plane).with_transient(10.0 * PI));
+            assert_eq!(settled.len(), 5);
+            assert!(settled.times.iter().all(|&t| t >= 10.0 * PI));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod random_matrix_theory {
-    use rand::Rng;
+    use rand::{Rng, SeedableRng};
+    use crate::seeding::seeding::{self, ChaosRng};
 
     #[derive(Debug)]
     pub struct Matrix {
@@ -18,8 +19,19 @@ pub mod random_matrix_theory {
             }
         }
         
+        /// Fills the matrix with random symmetric entries drawn from `seeding::next_rng`,
+        /// so they are reproducible under a fixed global seed
         pub fn random_gaussian(&mut self) {
-            let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
+            self.random_gaussian_with_rng(&mut seeding::next_rng());
+        }
+
+        /// Fills the matrix with random symmetric entries drawn from a generator seeded with `seed`
+        pub fn random_gaussian_seeded(&mut self, seed: u64) {
+            self.random_gaussian_with_rng(&mut ChaosRng::seed_from_u64(seed));
+        }
+
+        /// Fills the matrix with random symmetric entries drawn from the given generator
+        pub fn random_gaussian_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
             for i in 0..self.rows {
                 for j in 0..self.cols {
                     let v: f64 = rng.gen();
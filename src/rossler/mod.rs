@@ -1,5 +1,14 @@
 pub mod rossler {
     use plotters::prelude::*;
+    use crate::autodiff::autodiff::{GenericVectorField, Scalar};
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::{FixedStep, Integrator};
+    use crate::poincare::poincare::PoincareSection;
+    use crate::spectrum::spectrum::{PowerSpectrum, Welch};
+    use crate::stochastic::stochastic::{Noise, SdeIntegrator, SdeScheme};
+    use crate::trajectory::trajectory::{Sampling, Trajectory, TrajectoryStream};
+    #[derive(Debug, Clone)]
     pub struct RosslerSystem {
         pub x: f64,
         pub y: f64,
@@ -8,7 +17,9 @@ pub mod rossler {
         pub b: f64,
         pub c: f64,
         pub dt: f64,
-        pub steps: usize
+        pub steps: usize,
+        pub integrator: Integrator,
+        pub sampling: Sampling
     }
 
     impl RosslerSystem {
@@ -27,6 +38,7 @@ pub mod rossler {
         /// dt: small time increment used in each iteration of the simulation
         /// 
         /// steps: number of iterations in the simulation
+        #[allow(clippy::too_many_arguments)]
         pub fn new(x: f64, y: f64, z: f64, a: f64, b: f64, c: f64, dt: f64, steps: usize) -> RosslerSystem {
             RosslerSystem {
                 x, 
@@ -36,37 +48,88 @@ pub mod rossler {
                 b,
                 c,
                 dt,
-                steps
+                steps,
+                integrator: Integrator::Euler,
+                sampling: Sampling::default()
             }
         }
 
-        /// Returns the points of the Rossler System after the simulation is completed
-        pub fn simulate(&self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
-            let mut x_values: Vec<f64> = Vec::with_capacity(self.steps);
-            let mut y_values: Vec<f64> = Vec::with_capacity(self.steps);
-            let mut z_values: Vec<f64> = Vec::with_capacity(self.steps);
-
-            let mut x: f64 = self.x;
-            let mut y: f64 = self.y;
-            let mut z: f64 = self.z;
-
-            for _ in 0..self.steps {
-                x_values.push(x);
-                y_values.push(y);
-                z_values.push(z);
-
-                let dx: f64 = -y - z;
-                let dy: f64 = x + self.a * y;
-                let dz: f64 = self.b + z * (x - self.c);
-
-                x = x + self.dt * dx;
-                y = y + self.dt * dy;
-                z = z + self.dt * dz;
+        /// Sets the integration scheme used by `simulate`. Defaults to `Integrator::Euler`
+        pub fn with_integrator(mut self, integrator: Integrator) -> RosslerSystem {
+            self.integrator = integrator;
+            self
+        }
+
+        /// Discards the points of the simulation with times before the given duration. The discarded steps still count towards `steps`
+        pub fn with_transient(mut self, duration: f64) -> RosslerSystem {
+            self.sampling = self.sampling.with_transient(duration);
+            self
+        }
+
+        /// Keeps only every stride-th point of the simulation
+        pub fn with_stride(mut self, stride: usize) -> RosslerSystem {
+            self.sampling = self.sampling.with_stride(stride);
+            self
+        }
+
+        /// Returns a lazy stream over the points of the Rossler System, integrated with `self.integrator` for `self.steps` steps
+        pub fn stream(&self) -> TrajectoryStream<RosslerSystem> {
+            TrajectoryStream::new(self.clone(), self.integrator, self.dt)
+                .with_steps(self.steps)
+                .with_sampling(self.sampling)
+        }
+
+        /// Returns the trajectory of the Rossler System, integrated with `self.integrator`
+        pub fn simulate_trajectory(&self) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(self);
+            let mut state: Vec<f64> = vec![self.x, self.y, self.z];
+
+            for i in 0..self.steps {
+                let t: f64 = i as f64 * self.dt;
+                if self.sampling.keeps(i, self.dt) {
+                    trajectory.push(t, &state);
+                }
+                state = self.integrator.step(self, t, &state, self.dt);
             }
 
-            (x_values, y_values, z_values)
+            trajectory
+        }
+
+        /// Returns the points of the Rossler System after the simulation is completed, integrated with `self.integrator`
+        pub fn simulate(&self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+            self.simulate_trajectory().into()
         }
         
+        /// Returns the trajectory of the Rossler System driven by noise, for `self.steps` steps of size `self.dt` from the initial state
+        ///
+        /// *Params
+        ///
+        /// noise: intensity of the noise on each of x, y and z, either additive or multiplicative
+        ///
+        /// scheme: stochastic integration scheme
+        ///
+        /// seed: seed of the noise. Equal seeds produce identical trajectories
+        pub fn simulate_sde(&self, noise: Noise, scheme: SdeScheme, seed: u64) -> Trajectory {
+            SdeIntegrator::new(self, noise, scheme, self.dt, seed).simulate(self.steps, self.sampling)
+        }
+
+        /// Returns the points where the Rossler System crosses a Poincare section, integrated with `self.integrator`
+        /// for `self.steps` steps of size `self.dt` from the initial state. The crossings are located between
+        /// the steps, so they lie on the section plane
+        pub fn poincare_section(&self, section: &PoincareSection) -> Trajectory {
+            let mut solver: FixedStep = FixedStep::new(self.integrator, self.dt);
+            section
+                .compute(&mut solver, self, 0.0, &self.initial_state(), self.steps as f64 * self.dt)
+                .expect("fixed step integration cannot fail")
+        }
+
+        /// Returns the power spectral density of one state variable (0 for x, 1 for y, 2 for z) over the
+        /// points of `simulate_trajectory`. The spectrum has sharp lines while the motion is periodic and
+        /// broadens once it becomes chaotic
+        pub fn power_spectrum(&self, variable: usize, welch: &Welch) -> PowerSpectrum {
+            welch.estimate_trajectory(&self.simulate_trajectory(), variable)
+        }
+
         /// Displays the Rossler system trajectories
         pub fn display(&self, trajectories: (Vec<f64>, Vec<f64>, Vec<f64>)) {
             let root: DrawingArea<BitMapBackend<'_>, plotters::coord::Shift> = BitMapBackend::new("rossler_plot.png", (800, 600)).into_drawing_area();
@@ -77,7 +140,7 @@ pub mod rossler {
                 .margin(5)
                 .x_label_area_size(40)
                 .y_label_area_size(40)
-                .build_cartesian_2d(0.0..self.steps as f64, -20.0..20.0)
+                .build_cartesian_2d(self.sampling.transient_steps(self.dt) as f64..self.steps as f64, -20.0..20.0)
                 .unwrap();
 
             chart
@@ -88,24 +151,98 @@ pub mod rossler {
                 .unwrap();
 
             chart
-                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (i as f64, val)), &RED))
+                .draw_series(LineSeries::new(trajectories.0.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &RED))
                 .unwrap()
                 .label("X Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
             chart
-                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (i as f64, val)), &GREEN))
+                .draw_series(LineSeries::new(trajectories.1.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &GREEN))
                 .unwrap()
                 .label("Y Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], &GREEN));
+                .legend(|(x, y)| PathElement::new(vec![(x, y - 10), (x + 20, y - 10)], GREEN));
 
             chart
-                .draw_series(LineSeries::new(trajectories.2.iter().enumerate().map(|(i, &val)| (i as f64, val)), &BLUE))
+                .draw_series(LineSeries::new(trajectories.2.iter().enumerate().map(|(i, &val)| (self.sampling.step_of(i, self.dt) as f64, val)), &BLUE))
                 .unwrap()
                 .label("Z Trajectory")
-                .legend(|(x, y)| PathElement::new(vec![(x, y - 20), (x + 20, y - 20)], &BLUE));
+                .legend(|(x, y)| PathElement::new(vec![(x, y - 20), (x + 20, y - 20)], BLUE));
+
+            chart.configure_series_labels().background_style(WHITE.mix(0.8)).draw().unwrap();
+        }
+    }
+
+    impl DynamicalSystem for RosslerSystem {
+        fn dimension(&self) -> usize {
+            3
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            vec![("a", self.a), ("b", self.b), ("c", self.c)]
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            vec!["x".to_string(), "y".to_string(), "z".to_string()]
+        }
+
+        fn initial_state(&self) -> Vec<f64> {
+            vec![self.x, self.y, self.z]
+        }
+
+        fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+            self.vector_field(t, state)
+        }
+
+        fn jacobian(&self, _t: f64, state: &[f64]) -> Vec<Vec<f64>> {
+            vec![
+                vec![0.0, -1.0, -1.0],
+                vec![1.0, self.a, 0.0],
+                vec![state[2], 0.0, state[0] - self.c]
+            ]
+        }
+    }
+
+    impl GenericVectorField for RosslerSystem {
+        fn vector_field<T: Scalar>(&self, _t: f64, state: &[T]) -> Vec<T> {
+            let dx: T = -state[1] - state[2];
+            let dy: T = state[0] + state[1] * self.a;
+            let dz: T = state[2] * (state[0] - self.c) + self.b;
+            vec![dx, dy, dz]
+        }
+    }
+
+    /// Saves the parameters and the current state. `simulate_trajectory` restarts its step count on every
+    /// call, so to resume a run part way through checkpoint the `stream()` instead
+    impl Checkpointable for RosslerSystem {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("RosslerSystem");
+            checkpoint.put("x", self.x);
+            checkpoint.put("y", self.y);
+            checkpoint.put("z", self.z);
+            checkpoint.put("a", self.a);
+            checkpoint.put("b", self.b);
+            checkpoint.put("c", self.c);
+            checkpoint.put("dt", self.dt);
+            checkpoint.put("steps", self.steps);
+            checkpoint.put_section("integrator", &self.integrator.to_checkpoint());
+            checkpoint.put_section("sampling", &self.sampling.to_checkpoint());
+            checkpoint
+        }
 
-            chart.configure_series_labels().background_style(&WHITE.mix(0.8)).draw().unwrap();
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<RosslerSystem, CheckpointError> {
+            checkpoint.expect_kind("RosslerSystem")?;
+            Ok(RosslerSystem {
+                x: checkpoint.get("x")?,
+                y: checkpoint.get("y")?,
+                z: checkpoint.get("z")?,
+                a: checkpoint.get("a")?,
+                b: checkpoint.get("b")?,
+                c: checkpoint.get("c")?,
+                dt: checkpoint.get("dt")?,
+                steps: checkpoint.get("steps")?,
+                integrator: Integrator::from_checkpoint(&checkpoint.section("integrator"))?,
+                sampling: Sampling::from_checkpoint(&checkpoint.section("sampling"))?
+            })
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,327 @@
+pub mod spectrum {
+    use std::f64::consts::PI;
+    use plotters::prelude::*;
+    use crate::trajectory::trajectory::Trajectory;
+
+    /// Complex number used by the FFT
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct Complex {
+        pub re: f64,
+        pub im: f64
+    }
+
+    impl Complex {
+        /// Creates a Complex number
+        pub fn new(re: f64, im: f64) -> Complex {
+            Complex { re, im }
+        }
+
+        /// Returns the squared magnitude
+        pub fn norm_squared(&self) -> f64 {
+            self.re * self.re + self.im * self.im
+        }
+
+        fn add(self, other: Complex) -> Complex {
+            Complex::new(self.re + other.re, self.im + other.im)
+        }
+
+        fn sub(self, other: Complex) -> Complex {
+            Complex::new(self.re - other.re, self.im - other.im)
+        }
+
+        fn mul(self, other: Complex) -> Complex {
+            Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+        }
+    }
+
+    /// Computes the discrete Fourier transform X_k = sum_n x_n e^(-2 pi i k n / N) in place with the
+    /// iterative radix-2 Cooley-Tukey algorithm. The length must be a power of two
+    pub fn fft(data: &mut [Complex]) {
+        let n: usize = data.len();
+        assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+        // bit reversal permutation
+        let mut j: usize = 0;
+        for i in 1..n {
+            let mut bit: usize = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+
+        let mut length: usize = 2;
+        while length <= n {
+            let angle: f64 = -2.0 * PI / length as f64;
+            for start in (0..n).step_by(length) {
+                for k in 0..length / 2 {
+                    let twiddle: Complex = Complex::new((angle * k as f64).cos(), (angle * k as f64).sin());
+                    let even: Complex = data[start + k];
+                    let odd: Complex = data[start + k + length / 2].mul(twiddle);
+                    data[start + k] = even.add(odd);
+                    data[start + k + length / 2] = even.sub(odd);
+                }
+            }
+            length <<= 1;
+        }
+    }
+
+    /// Tapering applied to each segment before its transform to reduce spectral leakage
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Window {
+        /// No tapering. Narrowest peaks but the most leakage
+        Rectangular,
+        /// Raised cosine reaching zero at both ends
+        #[default]
+        Hann,
+        /// Raised cosine with lower first side lobe that does not reach zero at the ends
+        Hamming
+    }
+
+    impl Window {
+        /// Returns the coefficients of the window for a segment of the given length
+        pub fn coefficients(&self, length: usize) -> Vec<f64> {
+            let denominator: f64 = length.max(2) as f64 - 1.0;
+            (0..length)
+                .map(|n| {
+                    let phase: f64 = 2.0 * PI * n as f64 / denominator;
+                    match self {
+                        Window::Rectangular => 1.0,
+                        Window::Hann => 0.5 - 0.5 * phase.cos(),
+                        Window::Hamming => 0.54 - 0.46 * phase.cos()
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// One-sided power spectral density of a real signal
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PowerSpectrum {
+        /// Frequencies from 0 to the Nyquist frequency 1 / (2 dt), in cycles per unit time
+        pub frequencies: Vec<f64>,
+        /// Power per unit frequency at each frequency. Its integral over frequency is the variance of the signal
+        pub density: Vec<f64>,
+        /// Number of segments averaged
+        pub segments: usize
+    }
+
+    impl PowerSpectrum {
+        /// Returns the nonzero frequency with the highest power
+        pub fn peak_frequency(&self) -> Option<f64> {
+            (1..self.frequencies.len())
+                .max_by(|&a, &b| self.density[a].total_cmp(&self.density[b]))
+                .map(|k| self.frequencies[k])
+        }
+
+        /// Returns the total power, the integral of the density over frequency
+        pub fn total_power(&self) -> f64 {
+            let resolution: f64 = self.frequencies.get(1).copied().unwrap_or(0.0);
+            self.density.iter().sum::<f64>() * resolution
+        }
+
+        /// Plots the spectrum with the power on a logarithmic scale, leaving out the zero frequency
+        ///
+        /// *Params
+        ///
+        /// path: file the PNG is written to
+        ///
+        /// size: (width, height) of the image in pixels
+        ///
+        /// caption: title of the plot
+        pub fn display(&self, path: &str, size: (u32, u32), caption: &str) {
+            let points: Vec<(f64, f64)> = self
+                .frequencies
+                .iter()
+                .zip(self.density.iter())
+                .skip(1)
+                .filter(|(_, &density)| density > 0.0)
+                .map(|(&frequency, &density)| (frequency, density))
+                .collect();
+            let high_frequency: f64 = self.frequencies.last().copied().unwrap_or(1.0).max(f64::MIN_POSITIVE);
+            let (low, high): (f64, f64) = points
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &(_, density)| (low.min(density), high.max(density)));
+            let (low, high): (f64, f64) = if low.is_finite() { (low * 0.5, high * 2.0) } else { (1e-10, 1.0) };
+
+            let root: DrawingArea<BitMapBackend<'_>, plotters::coord::Shift> = BitMapBackend::new(path, size).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+
+            let mut chart = ChartBuilder::on(&root)
+                .caption(caption, ("Arial", 20).into_font())
+                .margin(5)
+                .x_label_area_size(40)
+                .y_label_area_size(60)
+                .build_cartesian_2d(0.0..high_frequency, (low..high).log_scale())
+                .unwrap();
+
+            chart
+                .configure_mesh()
+                .x_desc("Frequency")
+                .y_desc("Power Spectral Density")
+                .y_label_formatter(&|density| format!("{:.0e}", density))
+                .draw()
+                .unwrap();
+
+            chart
+                .draw_series(LineSeries::new(points, &BLUE))
+                .unwrap();
+
+            root.present().unwrap();
+        }
+    }
+
+    /// Welch estimator of the power spectral density: the signal is cut into overlapping segments,
+    /// each segment has its mean removed and is windowed and transformed, and the periodograms of the
+    /// segments are averaged to reduce the variance of the estimate
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Welch {
+        /// Number of samples per segment, a power of two. Sets the frequency resolution 1 / (segment_length dt)
+        pub segment_length: usize,
+        /// Fraction of each segment shared with the next one
+        pub overlap: f64,
+        pub window: Window
+    }
+
+    impl Welch {
+        /// Creates a Welch estimator with a Hann window and half overlapping segments
+        ///
+        /// *Params
+        ///
+        /// segment_length: number of samples per segment, a power of two
+        pub fn new(segment_length: usize) -> Welch {
+            assert!(segment_length >= 2 && segment_length.is_power_of_two(), "segment length must be a power of two");
+            Welch {
+                segment_length,
+                overlap: 0.5,
+                window: Window::Hann
+            }
+        }
+
+        /// Sets the fraction of each segment shared with the next one, in [0, 1)
+        pub fn with_overlap(mut self, overlap: f64) -> Welch {
+            assert!((0.0..1.0).contains(&overlap), "overlap must lie in [0, 1)");
+            self.overlap = overlap;
+            self
+        }
+
+        /// Sets the window applied to each segment
+        pub fn with_window(mut self, window: Window) -> Welch {
+            self.window = window;
+            self
+        }
+
+        /// Estimates the power spectral density of a signal sampled at a fixed interval
+        ///
+        /// *Params
+        ///
+        /// values: samples of the signal, at least `segment_length` of them
+        ///
+        /// dt: time between samples
+        pub fn estimate(&self, values: &[f64], dt: f64) -> PowerSpectrum {
+            let n: usize = self.segment_length;
+            assert!(values.len() >= n, "signal is shorter than one segment");
+            assert!(dt > 0.0, "dt must be positive");
+
+            let window: Vec<f64> = self.window.coefficients(n);
+            let window_power: f64 = window.iter().map(|w| w * w).sum();
+            let hop: usize = ((n as f64 * (1.0 - self.overlap)).round() as usize).max(1);
+            let bins: usize = n / 2 + 1;
+
+            let mut density: Vec<f64> = vec![0.0; bins];
+            let mut segments: usize = 0;
+            let mut buffer: Vec<Complex> = vec![Complex::default(); n];
+            for start in (0..=values.len() - n).step_by(hop) {
+                let segment: &[f64] = &values[start..start + n];
+                let mean: f64 = segment.iter().sum::<f64>() / n as f64;
+                for ((slot, value), w) in buffer.iter_mut().zip(segment.iter()).zip(window.iter()) {
+                    *slot = Complex::new((value - mean) * w, 0.0);
+                }
+                fft(&mut buffer);
+                for (total, coefficient) in density.iter_mut().zip(buffer.iter()) {
+                    *total += coefficient.norm_squared();
+                }
+                segments += 1;
+            }
+
+            // one-sided density: every bin except zero and Nyquist also holds the power of its negative frequency
+            let scale: f64 = dt / (window_power * segments as f64);
+            for (k, value) in density.iter_mut().enumerate() {
+                *value *= if k == 0 || k == n / 2 { scale } else { 2.0 * scale };
+            }
+
+            PowerSpectrum {
+                frequencies: (0..bins).map(|k| k as f64 / (n as f64 * dt)).collect(),
+                density,
+                segments
+            }
+        }
+
+        /// Estimates the power spectral density of one state variable of a trajectory, taking the
+        /// sampling interval from its first two times
+        pub fn estimate_trajectory(&self, trajectory: &Trajectory, variable: usize) -> PowerSpectrum {
+            assert!(trajectory.len() >= 2, "trajectory needs at least two points");
+            self.estimate(trajectory.column(variable), trajectory.times[1] - trajectory.times[0])
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use rand::{Rng, SeedableRng};
+        use crate::seeding::seeding::ChaosRng;
+
+        fn variance(values: &[f64]) -> f64 {
+            let mean: f64 = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64
+        }
+
+        #[test]
+        fn fft_matches_the_naive_dft() {
+            let mut rng: ChaosRng = ChaosRng::seed_from_u64(3);
+            let data: Vec<Complex> = (0..64).map(|_| Complex::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))).collect();
+            let mut transformed: Vec<Complex> = data.clone();
+            fft(&mut transformed);
+
+            for (k, coefficient) in transformed.iter().enumerate() {
+                let expected: Complex = data.iter().enumerate().fold(Complex::default(), |sum, (n, x)| {
+                    let angle: f64 = -2.0 * PI * (k * n) as f64 / data.len() as f64;
+                    sum.add(x.mul(Complex::new(angle.cos(), angle.sin())))
+                });
+                assert!(coefficient.sub(expected).norm_squared().sqrt() < 1e-12, "{}: {:?} against {:?}", k, coefficient, expected);
+            }
+        }
+
+        #[test]
+        fn sinusoid_peaks_at_its_frequency_bin() {
+            let dt: f64 = 0.01;
+            let n: usize = 1024;
+            let frequency: f64 = 51.0 / (n as f64 * dt);
+            let values: Vec<f64> = (0..8 * n).map(|i| 3.0 * (2.0 * PI * frequency * i as f64 * dt).sin() + 1.0).collect();
+
+            for window in [Window::Rectangular, Window::Hann, Window::Hamming] {
+                let spectrum: PowerSpectrum = Welch::new(n).with_window(window).estimate(&values, dt);
+                assert_eq!(spectrum.peak_frequency(), Some(spectrum.frequencies[51]));
+                assert!((spectrum.frequencies[51] - frequency).abs() < 1e-12);
+            }
+        }
+
+        #[test]
+        fn integrated_density_is_the_variance() {
+            let mut rng: ChaosRng = ChaosRng::seed_from_u64(4);
+            let values: Vec<f64> = (0..1 << 16).map(|_| rng.gen_range(-2.0..4.0)).collect();
+
+            // with a single unwindowed segment Parseval's theorem holds exactly
+            let single: PowerSpectrum = Welch::new(1 << 16).with_window(Window::Rectangular).estimate(&values, 0.1);
+            assert!((single.total_power() - variance(&values)).abs() < 1e-10 * variance(&values));
+
+            let averaged: PowerSpectrum = Welch::new(1024).estimate(&values, 0.1);
+            assert!((averaged.total_power() / variance(&values) - 1.0).abs() < 0.01, "{} against {}", averaged.total_power(), variance(&values));
+        }
+    }
+}
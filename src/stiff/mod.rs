@@ -0,0 +1,387 @@
+pub mod stiff {
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::{OdeSolver, SolverError, SolverStats};
+    use crate::linear_algebra::linear_algebra::LuDecomposition;
+
+    const SAFETY: f64 = 0.9;
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 5.0;
+    const MAX_NEWTON_ITERATIONS: usize = 10;
+    /// Points kept for the multistep formula: the current one and the two before it
+    const HISTORY_LENGTH: usize = 3;
+    /// Newton iterations stop once the scaled correction falls below this fraction of the tolerance
+    const NEWTON_TOLERANCE: f64 = 0.03;
+
+    /// Variable step backward differentiation formula solver of orders 1 and 2 for stiff systems.
+    ///
+    /// The implicit equation of each step is solved with a simplified Newton iteration using the
+    /// Jacobian supplied by `DynamicalSystem::jacobian`, which is analytic when the system provides
+    /// one and a finite difference approximation otherwise. The solver starts with backward Euler
+    /// and switches to BDF2 once enough steps have been accepted.
+    ///
+    /// A step that cannot be completed fails with `SolverError::ImplicitStageDidNotConverge` when the
+    /// Newton iteration kept failing down to the minimum step size, and with
+    /// `SolverError::StepSizeTooSmall` when the error control rejected it
+    #[derive(Debug, Clone)]
+    pub struct Bdf {
+        pub absolute_tolerance: f64,
+        pub relative_tolerance: f64,
+        pub min_step: f64,
+        pub max_step: f64,
+        pub initial_step: Option<f64>,
+        step_size: Option<f64>,
+        history: Vec<(f64, Vec<f64>)>,
+        stats: SolverStats
+    }
+
+    impl Bdf {
+        /// Creates a Bdf solver
+        ///
+        /// *Params
+        ///
+        /// absolute_tolerance: error allowed in each component regardless of its magnitude
+        ///
+        /// relative_tolerance: error allowed in each component relative to its magnitude
+        pub fn new(absolute_tolerance: f64, relative_tolerance: f64) -> Bdf {
+            Bdf {
+                absolute_tolerance,
+                relative_tolerance,
+                min_step: 1e-12,
+                max_step: f64::INFINITY,
+                initial_step: None,
+                step_size: None,
+                history: Vec::with_capacity(HISTORY_LENGTH),
+                stats: SolverStats::default()
+            }
+        }
+
+        /// Bounds the step sizes the error control may choose
+        pub fn with_step_bounds(mut self, min_step: f64, max_step: f64) -> Bdf {
+            self.min_step = min_step;
+            self.max_step = max_step;
+            self
+        }
+
+        /// Sets the size of the first attempted step. When unset it is estimated from the vector field
+        pub fn with_initial_step(mut self, initial_step: f64) -> Bdf {
+            self.initial_step = Some(initial_step);
+            self
+        }
+
+        /// Returns the step size the solver will attempt next
+        pub fn step_size(&self) -> Option<f64> {
+            self.step_size
+        }
+
+        fn estimate_initial_step(&self, state: &[f64], derivative: &[f64]) -> f64 {
+            let d0: f64 = self.norm(state, state);
+            let d1: f64 = self.norm(derivative, state);
+            let h: f64 = if d0 < 1e-5 || d1 < 1e-5 { 1e-6 } else { 0.01 * d0 / d1 };
+            h.clamp(self.min_step, self.max_step)
+        }
+
+        /// Root mean square of the components of v, each scaled by its tolerance
+        fn norm(&self, v: &[f64], state: &[f64]) -> f64 {
+            let sum: f64 = (0..v.len())
+                .map(|i| (v[i] / (self.absolute_tolerance + self.relative_tolerance * state[i].abs())).powi(2))
+                .sum();
+            (sum / v.len().max(1) as f64).sqrt()
+        }
+
+        /// Solves y = constant + gamma * h * f(t, y) by simplified Newton iteration from a predicted y.
+        /// Returns None when the iteration diverges or the iteration matrix is singular
+        fn newton(&mut self, system: &dyn DynamicalSystem, t: f64, predicted: &[f64], constant: &[f64], gamma_h: f64) -> Option<Vec<f64>> {
+            let n: usize = predicted.len();
+            let jacobian: Vec<Vec<f64>> = system.jacobian(t, predicted);
+            self.stats.jacobian_evaluations += 1;
+
+            let iteration_matrix: Vec<Vec<f64>> = (0..n)
+                .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 } - gamma_h * jacobian[i][j]).collect())
+                .collect();
+            let lu: LuDecomposition = LuDecomposition::new(iteration_matrix)?;
+
+            let mut y: Vec<f64> = predicted.to_vec();
+            let mut previous_correction: f64 = f64::INFINITY;
+            for _ in 0..MAX_NEWTON_ITERATIONS {
+                let derivative: Vec<f64> = system.derivative(t, &y);
+                self.stats.function_evaluations += 1;
+                self.stats.newton_iterations += 1;
+
+                let residual: Vec<f64> = (0..n).map(|i| constant[i] + gamma_h * derivative[i] - y[i]).collect();
+                let correction: Vec<f64> = lu.solve(&residual);
+                for i in 0..n {
+                    y[i] += correction[i];
+                }
+
+                let correction_norm: f64 = self.norm(&correction, &y);
+                if !correction_norm.is_finite() || correction_norm > 2.0 * previous_correction {
+                    return None;
+                }
+                if correction_norm <= NEWTON_TOLERANCE {
+                    return Some(y);
+                }
+                previous_correction = correction_norm;
+            }
+
+            None
+        }
+    }
+
+    impl OdeSolver for Bdf {
+        fn step(&mut self, system: &dyn DynamicalSystem, t: f64, state: &[f64], t_end: f64) -> Result<(f64, Vec<f64>), SolverError> {
+            let n: usize = state.len();
+
+            // the multistep history is only valid when continuing from the last accepted point
+            let continuing: bool = matches!(self.history.last(), Some((t_last, state_last)) if *t_last == t && state_last == state);
+            if !continuing {
+                self.history.clear();
+                self.history.push((t, state.to_vec()));
+            }
+
+            let derivative: Vec<f64> = system.derivative(t, state);
+            self.stats.function_evaluations += 1;
+
+            let mut h: f64 = match self.step_size {
+                Some(h) => h,
+                None => self.initial_step.unwrap_or_else(|| self.estimate_initial_step(state, &derivative))
+            };
+
+            let mut newton_failed: bool;
+            loop {
+                h = h.min(self.max_step);
+                let last_step: bool = h >= t_end - t;
+                if last_step {
+                    h = t_end - t;
+                }
+                let t_next: f64 = if last_step { t_end } else { t + h };
+                let order: usize = if self.history.len() >= HISTORY_LENGTH { 2 } else { 1 };
+
+                let (predicted, constant, gamma, error_constant): (Vec<f64>, Vec<f64>, f64, f64) = if order == 1 {
+                    // backward Euler, predicted with forward Euler
+                    let predicted: Vec<f64> = (0..n).map(|i| state[i] + h * derivative[i]).collect();
+                    (predicted, state.to_vec(), 1.0, 0.5)
+                } else {
+                    // variable step BDF2, predicted by quadratic extrapolation of the last three points
+                    let (t_previous, state_previous): &(f64, Vec<f64>) = &self.history[1];
+                    let (t_oldest, state_oldest): &(f64, Vec<f64>) = &self.history[0];
+                    let ratio: f64 = h / (t - t_previous);
+                    let a: f64 = (1.0 + ratio).powi(2) / (1.0 + 2.0 * ratio);
+                    let b: f64 = ratio.powi(2) / (1.0 + 2.0 * ratio);
+                    let constant: Vec<f64> = (0..n).map(|i| a * state[i] - b * state_previous[i]).collect();
+
+                    let nodes: [f64; 3] = [*t_oldest, *t_previous, t];
+                    let weights: Vec<f64> = (0..3)
+                        .map(|k| {
+                            (0..3)
+                                .filter(|&m| m != k)
+                                .map(|m| (t_next - nodes[m]) / (nodes[k] - nodes[m]))
+                                .product()
+                        })
+                        .collect();
+                    let predicted: Vec<f64> = (0..n)
+                        .map(|i| weights[0] * state_oldest[i] + weights[1] * state_previous[i] + weights[2] * state[i])
+                        .collect();
+                    (predicted, constant, (1.0 + ratio) / (1.0 + 2.0 * ratio), 2.0 / 11.0)
+                };
+
+                match self.newton(system, t_next, &predicted, &constant, gamma * h) {
+                    Some(state_next) => {
+                        let error: Vec<f64> = (0..n).map(|i| error_constant * (state_next[i] - predicted[i])).collect();
+                        let error_norm: f64 = self.norm(&error, &state_next);
+                        let exponent: f64 = -1.0 / (order as f64 + 1.0);
+
+                        if error_norm <= 1.0 {
+                            let factor: f64 = if error_norm == 0.0 { MAX_FACTOR } else { (SAFETY * error_norm.powf(exponent)).clamp(MIN_FACTOR, MAX_FACTOR) };
+                            self.stats.accepted_steps += 1;
+                            self.step_size = Some((h * factor).clamp(self.min_step, self.max_step));
+                            if self.history.len() == HISTORY_LENGTH {
+                                self.history.remove(0);
+                            }
+                            self.history.push((t_next, state_next.clone()));
+                            return Ok((t_next, state_next));
+                        }
+
+                        h *= (SAFETY * error_norm.powf(exponent)).max(MIN_FACTOR);
+                        newton_failed = false;
+                    }
+                    None => {
+                        h *= 0.25;
+                        newton_failed = true;
+                    }
+                }
+
+                self.stats.rejected_steps += 1;
+                if h < self.min_step {
+                    return Err(if newton_failed {
+                        SolverError::ImplicitStageDidNotConverge { t, step: h }
+                    } else {
+                        SolverError::StepSizeTooSmall { t, step: h }
+                    });
+                }
+            }
+        }
+
+        fn stats(&self) -> SolverStats {
+            self.stats
+        }
+
+        fn reset(&mut self) {
+            self.step_size = None;
+            self.history.clear();
+            self.stats = SolverStats::default();
+        }
+    }
+
+    /// Saves the tolerances together with the step size controller and the multistep history, so a
+    /// restored solver continues with the same order and takes exactly the steps the original would have
+    impl Checkpointable for Bdf {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("Bdf");
+            checkpoint.put("absolute_tolerance", self.absolute_tolerance);
+            checkpoint.put("relative_tolerance", self.relative_tolerance);
+            checkpoint.put("min_step", self.min_step);
+            checkpoint.put("max_step", self.max_step);
+            checkpoint.put_optional("initial_step", self.initial_step);
+            checkpoint.put_optional("step_size", self.step_size);
+            checkpoint.put("history_length", self.history.len());
+            for (index, (t, state)) in self.history.iter().enumerate() {
+                checkpoint.put(&format!("history_{}.t", index), t);
+                checkpoint.put_list(&format!("history_{}.state", index), state);
+            }
+            checkpoint.put_section("stats", &self.stats.to_checkpoint());
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Bdf, CheckpointError> {
+            checkpoint.expect_kind("Bdf")?;
+            // BDF2 keeps the current point and the two before it, so a longer history was not written by this solver
+            let history_length: usize = checkpoint.get("history_length")?;
+            if history_length > HISTORY_LENGTH {
+                return Err(CheckpointError::Invalid {
+                    key: "history_length".to_string(),
+                    value: history_length.to_string()
+                });
+            }
+            let mut history: Vec<(f64, Vec<f64>)> = Vec::with_capacity(HISTORY_LENGTH);
+            for index in 0..history_length {
+                let key: String = format!("history_{}.state", index);
+                let state: Vec<f64> = checkpoint.get_list(&key)?;
+                if history.first().is_some_and(|(_, first): &(f64, Vec<f64>)| first.len() != state.len()) {
+                    let value: String = checkpoint.raw(&key)?.to_string();
+                    return Err(CheckpointError::Invalid { key, value });
+                }
+                history.push((checkpoint.get(&format!("history_{}.t", index))?, state));
+            }
+            Ok(Bdf {
+                absolute_tolerance: checkpoint.get("absolute_tolerance")?,
+                relative_tolerance: checkpoint.get("relative_tolerance")?,
+                min_step: checkpoint.get("min_step")?,
+                max_step: checkpoint.get("max_step")?,
+                initial_step: checkpoint.get_optional("initial_step")?,
+                step_size: checkpoint.get_optional("step_size")?,
+                history,
+                stats: SolverStats::from_checkpoint(&checkpoint.section("stats"))?
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::dynamical_system::dynamical_system::test_systems::{ExponentialDecay, VanDerPol};
+        use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+        use crate::integrators::integrators::Solution;
+
+        /// Exponential decay whose vector field is undefined after t = 0.5
+        struct Breakdown;
+
+        impl DynamicalSystem for Breakdown {
+            fn dimension(&self) -> usize {
+                1
+            }
+
+            fn parameters(&self) -> Vec<(&'static str, f64)> {
+                Vec::new()
+            }
+
+            fn initial_state(&self) -> Vec<f64> {
+                vec![1.0]
+            }
+
+            fn derivative(&self, t: f64, state: &[f64]) -> Vec<f64> {
+                vec![if t > 0.5 { f64::NAN } else { -state[0] }]
+            }
+        }
+
+        #[test]
+        fn stiff_van_der_pol_takes_few_steps() {
+            // one relaxation period of the mu = 1000 oscillator lasts about (3 - 2 ln 2) mu = 1614
+            let system: VanDerPol = VanDerPol { mu: 1000.0 };
+            let solution: Solution = Bdf::new(1e-6, 1e-6).solve(&system, 0.0, &system.initial_state(), 3000.0).unwrap();
+            let stats: SolverStats = solution.stats;
+            assert_eq!(*solution.times.last().unwrap(), 3000.0);
+            assert!(stats.accepted_steps < 10_000, "took {} steps", stats.accepted_steps);
+            assert!(stats.rejected_steps < stats.accepted_steps);
+            assert!(solution.states.iter().all(|state| state[0].abs() < 2.1));
+        }
+
+        #[test]
+        fn exponential_decay_end_point_is_within_tolerance() {
+            let system: ExponentialDecay = ExponentialDecay { rate: 1.0 };
+            let solution: Solution = Bdf::new(1e-8, 1e-8).solve(&system, 0.0, &[1.0], 2.0).unwrap();
+            assert!((solution.states.last().unwrap()[0] - system.solution(2.0)[0]).abs() < 1e-5);
+        }
+
+        #[test]
+        fn newton_failure_is_told_apart_from_error_control() {
+            let result: Result<Solution, SolverError> = Bdf::new(1e-6, 1e-6).with_initial_step(0.1).solve(&Breakdown, 0.0, &[1.0], 1.0);
+            assert!(matches!(result, Err(SolverError::ImplicitStageDidNotConverge { .. })), "{:?}", result.map(|solution| solution.stats));
+
+            let result: Result<Solution, SolverError> = Bdf::new(1e-6, 1e-6).with_step_bounds(0.1, 1.0).solve(&VanDerPol { mu: 1000.0 }, 0.0, &[2.0, 0.0], 3000.0);
+            assert!(matches!(result, Err(SolverError::StepSizeTooSmall { .. })), "{:?}", result.map(|solution| solution.stats));
+        }
+
+        /// Takes the given number of steps from (t, state) and returns every point reached
+        fn steps(solver: &mut Bdf, system: &VanDerPol, mut t: f64, mut state: Vec<f64>, count: usize) -> Vec<(f64, Vec<f64>)> {
+            let mut points: Vec<(f64, Vec<f64>)> = Vec::with_capacity(count);
+            for _ in 0..count {
+                (t, state) = solver.step(system, t, &state, 3000.0).unwrap();
+                points.push((t, state.clone()));
+            }
+            points
+        }
+
+        #[test]
+        fn resumed_solver_takes_the_same_steps() {
+            let system: VanDerPol = VanDerPol { mu: 1000.0 };
+            let mut reference: Bdf = Bdf::new(1e-6, 1e-6);
+            let uninterrupted: Vec<(f64, Vec<f64>)> = steps(&mut reference, &system, 0.0, vec![2.0, 0.0], 400);
+
+            let mut solver: Bdf = Bdf::new(1e-6, 1e-6);
+            let mut resumed: Vec<(f64, Vec<f64>)> = steps(&mut solver, &system, 0.0, vec![2.0, 0.0], 200);
+            let path: std::path::PathBuf = std::env::temp_dir().join(format!("chaos_theory_bdf_{}.checkpoint", std::process::id()));
+            solver.save(&path).unwrap();
+            let mut restored: Bdf = Bdf::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            let (t, state): (f64, Vec<f64>) = resumed.last().unwrap().clone();
+            resumed.extend(steps(&mut restored, &system, t, state, 200));
+            assert_eq!(resumed, uninterrupted);
+            assert_eq!(restored.stats(), reference.stats());
+        }
+
+        #[test]
+        fn history_longer_than_bdf2_keeps_is_rejected() {
+            let mut solver: Bdf = Bdf::new(1e-6, 1e-6);
+            steps(&mut solver, &VanDerPol { mu: 1.0 }, 0.0, vec![2.0, 0.0], 5);
+            let mut checkpoint: Checkpoint = solver.to_checkpoint();
+            assert!(Bdf::from_checkpoint(&checkpoint).is_ok());
+
+            checkpoint.put("history_length", 4);
+            checkpoint.put("history_3.t", 1.0);
+            checkpoint.put_list("history_3.state", &[0.0, 0.0]);
+            assert!(matches!(Bdf::from_checkpoint(&checkpoint), Err(CheckpointError::Invalid { key, .. }) if key == "history_length"));
+        }
+    }
+}
@@ -0,0 +1,330 @@
+pub mod stochastic {
+    use rand::{Rng, SeedableRng};
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::seeding::seeding::ChaosRng;
+    use crate::trajectory::trajectory::{Sampling, Trajectory};
+
+    /// How the noise acting on each component scales with the state
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum NoiseKind {
+        /// g_i(x) = sigma_i
+        Additive,
+        /// g_i(x) = sigma_i * x_i
+        Multiplicative
+    }
+
+    /// Diagonal noise term of a stochastic differential equation dx_i = f_i(t, x) dt + g_i(x) dW_i
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Noise {
+        pub intensities: Vec<f64>,
+        pub kind: NoiseKind
+    }
+
+    impl Noise {
+        /// Creates additive noise with one intensity per state component
+        pub fn additive(intensities: Vec<f64>) -> Noise {
+            Noise {
+                intensities,
+                kind: NoiseKind::Additive
+            }
+        }
+
+        /// Creates multiplicative noise with one intensity per state component
+        pub fn multiplicative(intensities: Vec<f64>) -> Noise {
+            Noise {
+                intensities,
+                kind: NoiseKind::Multiplicative
+            }
+        }
+
+        /// Returns g_i(x) for every component
+        pub fn diffusion(&self, state: &[f64]) -> Vec<f64> {
+            match self.kind {
+                NoiseKind::Additive => self.intensities.clone(),
+                NoiseKind::Multiplicative => self.intensities.iter().zip(state.iter()).map(|(s, x)| s * x).collect()
+            }
+        }
+
+        /// Returns dg_i / dx_i for every component
+        pub fn diffusion_derivative(&self) -> Vec<f64> {
+            match self.kind {
+                NoiseKind::Additive => vec![0.0; self.intensities.len()],
+                NoiseKind::Multiplicative => self.intensities.clone()
+            }
+        }
+    }
+
+    /// Integration schemes for stochastic differential equations
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SdeScheme {
+        /// Euler-Maruyama, strong order 0.5 and weak order 1
+        EulerMaruyama,
+        /// Milstein, strong order 1 for diagonal noise
+        Milstein,
+        /// Simplified weak Euler with two point distributed increments, weak order 1.
+        /// Only the statistics of an ensemble of its paths are meaningful
+        WeakEuler
+    }
+
+    impl Checkpointable for Noise {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("Noise");
+            checkpoint.put_list("intensities", &self.intensities);
+            checkpoint.put("noise_kind", format!("{:?}", self.kind));
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Noise, CheckpointError> {
+            checkpoint.expect_kind("Noise")?;
+            let kind: NoiseKind = match checkpoint.raw("noise_kind")? {
+                "Additive" => NoiseKind::Additive,
+                "Multiplicative" => NoiseKind::Multiplicative,
+                value => return Err(CheckpointError::Invalid { key: "noise_kind".to_string(), value: value.to_string() })
+            };
+            Ok(Noise {
+                intensities: checkpoint.get_list("intensities")?,
+                kind
+            })
+        }
+    }
+
+    /// Integrates a system with added noise, one step at a time, from a seeded random number generator.
+    ///
+    /// When the system is `Checkpointable` the integrator is too, including the state of its random
+    /// number generator, so a restored run draws exactly the noise the original would have drawn
+    pub struct SdeIntegrator<S: DynamicalSystem> {
+        system: S,
+        noise: Noise,
+        scheme: SdeScheme,
+        dt: f64,
+        t: f64,
+        state: Vec<f64>,
+        steps_taken: usize,
+        rng: ChaosRng
+    }
+
+    impl<S: DynamicalSystem> SdeIntegrator<S> {
+        /// Creates an SdeIntegrator starting from the initial state of a system at t = 0
+        ///
+        /// *Params
+        ///
+        /// system: system providing the drift f(t, x)
+        ///
+        /// noise: diffusion g(x), with one intensity per state component
+        ///
+        /// scheme: integration scheme
+        ///
+        /// dt: size of each step
+        ///
+        /// seed: seed of the noise. Equal seeds produce identical paths
+        pub fn new(system: S, noise: Noise, scheme: SdeScheme, dt: f64, seed: u64) -> SdeIntegrator<S> {
+            assert_eq!(noise.intensities.len(), system.dimension(), "one noise intensity is needed per state component");
+            let state: Vec<f64> = system.initial_state();
+            SdeIntegrator {
+                system,
+                noise,
+                scheme,
+                dt,
+                t: 0.0,
+                state,
+                steps_taken: 0,
+                rng: ChaosRng::seed_from_u64(seed)
+            }
+        }
+
+        /// Replaces the noise generator, for example with one from `seeding::next_rng`
+        pub fn with_rng(mut self, rng: ChaosRng) -> SdeIntegrator<S> {
+            self.rng = rng;
+            self
+        }
+
+        /// Returns the current time
+        pub fn time(&self) -> f64 {
+            self.t
+        }
+
+        /// Returns the current state
+        pub fn state(&self) -> &[f64] {
+            &self.state
+        }
+
+        /// Returns the number of steps taken so far
+        pub fn steps_taken(&self) -> usize {
+            self.steps_taken
+        }
+
+        /// Advances the state by a single step
+        pub fn step(&mut self) {
+            let drift: Vec<f64> = self.system.derivative(self.t, &self.state);
+            let diffusion: Vec<f64> = self.noise.diffusion(&self.state);
+            let diffusion_derivative: Vec<f64> = self.noise.diffusion_derivative();
+
+            for i in 0..self.state.len() {
+                let increment: f64 = match self.scheme {
+                    SdeScheme::WeakEuler => if self.rng.gen::<bool>() { self.dt.sqrt() } else { -self.dt.sqrt() },
+                    _ => self.dt.sqrt() * standard_normal(&mut self.rng)
+                };
+                let mut change: f64 = drift[i] * self.dt + diffusion[i] * increment;
+                if self.scheme == SdeScheme::Milstein {
+                    change += 0.5 * diffusion[i] * diffusion_derivative[i] * (increment.powi(2) - self.dt);
+                }
+                self.state[i] += change;
+            }
+
+            self.steps_taken += 1;
+            self.t = self.steps_taken as f64 * self.dt;
+        }
+
+        /// Takes the given number of steps and returns the points kept by the sampling, in the same form as the deterministic simulations
+        ///
+        /// *Params
+        ///
+        /// steps: number of steps
+        ///
+        /// sampling: transient and stride applied to the recorded points
+        pub fn simulate(&mut self, steps: usize, sampling: Sampling) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(&self.system);
+            let first_step: usize = self.steps_taken;
+
+            for i in 0..steps {
+                if sampling.keeps(first_step + i, self.dt) {
+                    trajectory.push(self.t, &self.state);
+                }
+                self.step();
+            }
+
+            trajectory
+        }
+    }
+
+    /// Draws a standard normal sample with the Box-Muller transform
+    pub fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+        let u1: f64 = 1.0 - rng.gen::<f64>();
+        let u2: f64 = rng.gen::<f64>();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    impl<S: DynamicalSystem + Checkpointable> Checkpointable for SdeIntegrator<S> {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("SdeIntegrator");
+            checkpoint.put_section("system", &self.system.to_checkpoint());
+            checkpoint.put_section("noise", &self.noise.to_checkpoint());
+            checkpoint.put("scheme", format!("{:?}", self.scheme));
+            checkpoint.put("dt", self.dt);
+            checkpoint.put("t", self.t);
+            checkpoint.put_list("state", &self.state);
+            checkpoint.put("steps_taken", self.steps_taken);
+            checkpoint.put_section("rng", &self.rng.to_checkpoint());
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<SdeIntegrator<S>, CheckpointError> {
+            checkpoint.expect_kind("SdeIntegrator")?;
+            let scheme: SdeScheme = match checkpoint.raw("scheme")? {
+                "EulerMaruyama" => SdeScheme::EulerMaruyama,
+                "Milstein" => SdeScheme::Milstein,
+                "WeakEuler" => SdeScheme::WeakEuler,
+                value => return Err(CheckpointError::Invalid { key: "scheme".to_string(), value: value.to_string() })
+            };
+            Ok(SdeIntegrator {
+                system: S::from_checkpoint(&checkpoint.section("system"))?,
+                noise: Noise::from_checkpoint(&checkpoint.section("noise"))?,
+                scheme,
+                dt: checkpoint.get("dt")?,
+                t: checkpoint.get("t")?,
+                state: checkpoint.get_list("state")?,
+                steps_taken: checkpoint.get("steps_taken")?,
+                rng: ChaosRng::from_checkpoint(&checkpoint.section("rng"))?
+            })
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::dynamical_system::dynamical_system::test_systems::ExponentialDecay;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        /// Returns the mean absolute error at t = 1 of a scheme on geometric Brownian motion
+        /// dx = mu x dt + sigma x dW from x = 1, against the exact solution on the same Wiener path
+        fn gbm_strong_error(scheme: SdeScheme, steps: usize, paths: u64) -> f64 {
+            let (mu, sigma): (f64, f64) = (1.5, 1.0);
+            let dt: f64 = 1.0 / steps as f64;
+            let total: f64 = (0..paths)
+                .map(|seed| {
+                    let mut integrator: SdeIntegrator<ExponentialDecay> = SdeIntegrator::new(ExponentialDecay { rate: -mu }, Noise::multiplicative(vec![sigma]), scheme, dt, seed);
+                    for _ in 0..steps {
+                        integrator.step();
+                    }
+                    // the integrator draws one normal increment per step from the same generator
+                    let mut rng: ChaosRng = ChaosRng::seed_from_u64(seed);
+                    let wiener: f64 = (0..steps).map(|_| dt.sqrt() * standard_normal(&mut rng)).sum();
+                    let exact: f64 = ((mu - 0.5 * sigma * sigma) + sigma * wiener).exp();
+                    (integrator.state()[0] - exact).abs()
+                })
+                .sum();
+            total / paths as f64
+        }
+
+        /// Returns the order of convergence between two step counts sixteen times apart
+        fn strong_order(scheme: SdeScheme) -> f64 {
+            (gbm_strong_error(scheme, 16, 500) / gbm_strong_error(scheme, 256, 500)).ln() / 16.0_f64.ln()
+        }
+
+        #[test]
+        fn milstein_converges_faster_than_euler_maruyama() {
+            let milstein: f64 = strong_order(SdeScheme::Milstein);
+            let euler_maruyama: f64 = strong_order(SdeScheme::EulerMaruyama);
+            assert!((milstein - 1.0).abs() < 0.2, "Milstein strong order {}", milstein);
+            assert!((euler_maruyama - 0.5).abs() < 0.2, "Euler-Maruyama strong order {}", euler_maruyama);
+        }
+
+        #[test]
+        fn weak_euler_reproduces_the_ornstein_uhlenbeck_moments() {
+            // dx = -theta x dt + sigma dW from x = 1 has mean e^(-theta t) and variance sigma^2 (1 - e^(-2 theta t)) / (2 theta)
+            let (theta, sigma, paths): (f64, f64, u64) = (1.0, 0.5, 20000);
+            let finals: Vec<f64> = (0..paths)
+                .map(|seed| {
+                    let mut integrator: SdeIntegrator<ExponentialDecay> = SdeIntegrator::new(ExponentialDecay { rate: theta }, Noise::additive(vec![sigma]), SdeScheme::WeakEuler, 0.01, seed);
+                    for _ in 0..100 {
+                        integrator.step();
+                    }
+                    integrator.state()[0]
+                })
+                .collect();
+
+            let mean: f64 = finals.iter().sum::<f64>() / paths as f64;
+            let variance: f64 = finals.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (paths - 1) as f64;
+            assert!((mean - (-theta).exp()).abs() < 0.01, "mean {}", mean);
+            assert!((variance - sigma * sigma * (1.0 - (-2.0 * theta).exp()) / (2.0 * theta)).abs() < 0.005, "variance {}", variance);
+        }
+
+        #[test]
+        fn same_seed_reproduces_the_path_across_a_checkpoint() {
+            let lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 0);
+            let noise: Noise = Noise::additive(vec![1.0, 1.0, 1.0]);
+            for scheme in [SdeScheme::EulerMaruyama, SdeScheme::Milstein, SdeScheme::WeakEuler] {
+                let uninterrupted: Trajectory = SdeIntegrator::new(lorenz.clone(), noise.clone(), scheme, 0.01, 11).simulate(1000, Sampling::default());
+                let repeated: Trajectory = SdeIntegrator::new(lorenz.clone(), noise.clone(), scheme, 0.01, 11).simulate(1000, Sampling::default());
+                let other: Trajectory = SdeIntegrator::new(lorenz.clone(), noise.clone(), scheme, 0.01, 12).simulate(1000, Sampling::default());
+                assert_eq!(repeated, uninterrupted);
+                assert_ne!(other.columns, uninterrupted.columns);
+
+                let mut integrator: SdeIntegrator<LorenzSystem> = SdeIntegrator::new(lorenz.clone(), noise.clone(), scheme, 0.01, 11);
+                let mut resumed: Trajectory = integrator.simulate(500, Sampling::default());
+                let path: std::path::PathBuf = std::env::temp_dir().join(format!("chaos_theory_sde_{}_{:?}.checkpoint", std::process::id(), scheme));
+                integrator.save(&path).unwrap();
+                let mut restored: SdeIntegrator<LorenzSystem> = SdeIntegrator::load(&path).unwrap();
+                std::fs::remove_file(&path).unwrap();
+
+                let rest: Trajectory = restored.simulate(500, Sampling::default());
+                for i in 0..rest.len() {
+                    resumed.push(rest.times[i], &rest.point(i));
+                }
+                assert_eq!(resumed, uninterrupted);
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+pub mod symplectic {
+    /// Tolerance at which the fixed point iterations of the implicit stages are considered converged
+    const FIXED_POINT_TOLERANCE: f64 = 1e-14;
+    const MAX_FIXED_POINT_ITERATIONS: usize = 100;
+
+    /// A system described by a Hamiltonian H(q, p) in canonical coordinates q and momenta p
+    pub trait HamiltonianSystem {
+        /// Returns the number of degrees of freedom, the length of both q and p
+        fn degrees_of_freedom(&self) -> usize;
+
+        /// Returns the total energy H(q, p)
+        fn hamiltonian(&self, q: &[f64], p: &[f64]) -> f64;
+
+        /// Returns the partial derivatives of H with respect to q, which are -dp/dt
+        fn dh_dq(&self, q: &[f64], p: &[f64]) -> Vec<f64>;
+
+        /// Returns the partial derivatives of H with respect to p, which are dq/dt
+        fn dh_dp(&self, q: &[f64], p: &[f64]) -> Vec<f64>;
+    }
+
+    /// Symplectic and time reversible integration schemes. All of them are implicit for Hamiltonians
+    /// that are not separable, and solve their implicit stages by fixed point iteration
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SymplecticIntegrator {
+        /// Generalized Stormer-Verlet (leapfrog), second order
+        StormerVerlet,
+        /// Yoshida's triple jump composition of Stormer-Verlet, fourth order
+        Yoshida4,
+        /// Implicit midpoint rule, second order
+        ImplicitMidpoint
+    }
+
+    impl SymplecticIntegrator {
+        /// Advances (q, p) by a single step and returns the new (q, p), or None when the fixed point
+        /// iteration of an implicit stage does not converge, which happens when the step is too large
+        ///
+        /// *Params
+        ///
+        /// system: Hamiltonian system being integrated
+        ///
+        /// q: generalized coordinates at the start of the step
+        ///
+        /// p: conjugate momenta at the start of the step
+        ///
+        /// dt: size of the step
+        pub fn step<H: HamiltonianSystem + ?Sized>(&self, system: &H, q: &[f64], p: &[f64], dt: f64) -> Option<(Vec<f64>, Vec<f64>)> {
+            match self {
+                SymplecticIntegrator::StormerVerlet => stormer_verlet(system, q, p, dt),
+                SymplecticIntegrator::Yoshida4 => {
+                    let cube_root: f64 = 2.0_f64.powf(1.0 / 3.0);
+                    let w1: f64 = 1.0 / (2.0 - cube_root);
+                    let w0: f64 = -cube_root / (2.0 - cube_root);
+                    let (q1, p1): (Vec<f64>, Vec<f64>) = stormer_verlet(system, q, p, w1 * dt)?;
+                    let (q2, p2): (Vec<f64>, Vec<f64>) = stormer_verlet(system, &q1, &p1, w0 * dt)?;
+                    stormer_verlet(system, &q2, &p2, w1 * dt)
+                }
+                SymplecticIntegrator::ImplicitMidpoint => implicit_midpoint(system, q, p, dt)
+            }
+        }
+    }
+
+    /// Returns (E_i - E_0) / |E_0| for every entry of an energy series
+    pub fn relative_energy_drift(energies: &[f64]) -> Vec<f64> {
+        match energies.first() {
+            Some(&initial) => energies.iter().map(|e| (e - initial) / initial.abs()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    fn stormer_verlet<H: HamiltonianSystem + ?Sized>(system: &H, q: &[f64], p: &[f64], dt: f64) -> Option<(Vec<f64>, Vec<f64>)> {
+        let half: f64 = 0.5 * dt;
+
+        // p_half = p - dt / 2 * dH/dq(q, p_half)
+        let p_half: Vec<f64> = fixed_point(p.to_vec(), |p_half| {
+            let dq: Vec<f64> = system.dh_dq(q, p_half);
+            (0..p.len()).map(|i| p[i] - half * dq[i]).collect()
+        })?;
+
+        // q_next = q + dt / 2 * (dH/dp(q, p_half) + dH/dp(q_next, p_half))
+        let velocity: Vec<f64> = system.dh_dp(q, &p_half);
+        let q_next: Vec<f64> = fixed_point(q.to_vec(), |q_next| {
+            let velocity_next: Vec<f64> = system.dh_dp(q_next, &p_half);
+            (0..q.len()).map(|i| q[i] + half * (velocity[i] + velocity_next[i])).collect()
+        })?;
+
+        let dq: Vec<f64> = system.dh_dq(&q_next, &p_half);
+        let p_next: Vec<f64> = (0..p.len()).map(|i| p_half[i] - half * dq[i]).collect();
+        Some((q_next, p_next))
+    }
+
+    fn implicit_midpoint<H: HamiltonianSystem + ?Sized>(system: &H, q: &[f64], p: &[f64], dt: f64) -> Option<(Vec<f64>, Vec<f64>)> {
+        let n: usize = q.len();
+        let mut initial: Vec<f64> = q.to_vec();
+        initial.extend_from_slice(p);
+
+        let next: Vec<f64> = fixed_point(initial, |z| {
+            let q_mid: Vec<f64> = (0..n).map(|i| 0.5 * (q[i] + z[i])).collect();
+            let p_mid: Vec<f64> = (0..n).map(|i| 0.5 * (p[i] + z[n + i])).collect();
+            let dp: Vec<f64> = system.dh_dp(&q_mid, &p_mid);
+            let dq: Vec<f64> = system.dh_dq(&q_mid, &p_mid);
+            let mut z_next: Vec<f64> = (0..n).map(|i| q[i] + dt * dp[i]).collect();
+            z_next.extend((0..n).map(|i| p[i] - dt * dq[i]));
+            z_next
+        })?;
+
+        Some((next[..n].to_vec(), next[n..].to_vec()))
+    }
+
+    /// Iterates x = map(x) from an initial guess until successive iterates agree. Returns None when
+    /// they still differ after the iteration limit, in which case the map is not a contraction at this
+    /// step size and the last iterate is not a solution of the implicit stage
+    fn fixed_point<F: Fn(&[f64]) -> Vec<f64>>(mut x: Vec<f64>, map: F) -> Option<Vec<f64>> {
+        for _ in 0..MAX_FIXED_POINT_ITERATIONS {
+            let x_next: Vec<f64> = map(&x);
+            let change: f64 = x_next.iter().zip(x.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            let scale: f64 = x_next.iter().map(|a| a.abs()).fold(1.0, f64::max);
+            x = x_next;
+            if change <= FIXED_POINT_TOLERANCE * scale {
+                return Some(x);
+            }
+        }
+        None
+    }
+}
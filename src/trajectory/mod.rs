@@ -0,0 +1,451 @@
+pub mod trajectory {
+    use std::ops::Range;
+    use crate::checkpoint::checkpoint::{Checkpoint, CheckpointError, Checkpointable};
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::{Integrator, Solution};
+
+    /// Time series of a system's state, stored column by column, together with the names of the
+    /// state variables and the parameters that generated it
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Trajectory {
+        pub times: Vec<f64>,
+        pub columns: Vec<Vec<f64>>,
+        pub variable_names: Vec<String>,
+        pub parameters: Vec<(String, f64)>
+    }
+
+    impl Trajectory {
+        /// Creates an empty Trajectory
+        ///
+        /// *Params
+        ///
+        /// variable_names: name of each state variable, which also fixes the dimension
+        ///
+        /// parameters: named parameters that generated the trajectory
+        pub fn new(variable_names: Vec<String>, parameters: Vec<(String, f64)>) -> Trajectory {
+            Trajectory {
+                times: Vec::new(),
+                columns: vec![Vec::new(); variable_names.len()],
+                variable_names,
+                parameters
+            }
+        }
+
+        /// Creates an empty Trajectory labelled with the variable names and parameters of a system
+        pub fn for_system<S: DynamicalSystem + ?Sized>(system: &S) -> Trajectory {
+            Trajectory::new(
+                system.variable_names(),
+                system.parameters().into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+            )
+        }
+
+        /// Creates a Trajectory from the output of a solver run on a system
+        pub fn from_solution<S: DynamicalSystem + ?Sized>(system: &S, solution: &Solution) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(system);
+            for (t, state) in solution.times.iter().zip(solution.states.iter()) {
+                trajectory.push(*t, state);
+            }
+            trajectory
+        }
+
+        /// Appends a point to the end of the trajectory
+        pub fn push(&mut self, t: f64, state: &[f64]) {
+            assert_eq!(state.len(), self.columns.len(), "state does not match the trajectory dimension");
+            self.times.push(t);
+            for (column, value) in self.columns.iter_mut().zip(state.iter()) {
+                column.push(*value);
+            }
+        }
+
+        /// Returns the number of points
+        pub fn len(&self) -> usize {
+            self.times.len()
+        }
+
+        /// Returns whether the trajectory has no points
+        pub fn is_empty(&self) -> bool {
+            self.times.is_empty()
+        }
+
+        /// Returns the number of state variables
+        pub fn dimension(&self) -> usize {
+            self.columns.len()
+        }
+
+        /// Returns the time series of a single state variable
+        pub fn column(&self, index: usize) -> &[f64] {
+            &self.columns[index]
+        }
+
+        /// Returns the time series of the state variable with the given name
+        pub fn column_by_name(&self, name: &str) -> Option<&[f64]> {
+            self.variable_names.iter().position(|n| n == name).map(|index| self.column(index))
+        }
+
+        /// Returns the value of a named parameter
+        pub fn parameter(&self, name: &str) -> Option<f64> {
+            self.parameters.iter().find(|(n, _)| n == name).map(|(_, value)| *value)
+        }
+
+        /// Returns the state at the given point index
+        pub fn point(&self, index: usize) -> Vec<f64> {
+            self.columns.iter().map(|column| column[index]).collect()
+        }
+
+        /// Returns an iterator over (time, state) pairs
+        pub fn points(&self) -> Points<'_> {
+            Points {
+                trajectory: self,
+                index: 0
+            }
+        }
+
+        /// Returns a new Trajectory holding the points in the given index range
+        pub fn slice(&self, range: Range<usize>) -> Trajectory {
+            Trajectory {
+                times: self.times[range.clone()].to_vec(),
+                columns: self.columns.iter().map(|column| column[range.clone()].to_vec()).collect(),
+                variable_names: self.variable_names.clone(),
+                parameters: self.parameters.clone()
+            }
+        }
+
+        /// Returns a new Trajectory holding the points with times in [t_start, t_end]
+        pub fn slice_time(&self, t_start: f64, t_end: f64) -> Trajectory {
+            let start: usize = self.times.partition_point(|&t| t < t_start);
+            let end: usize = self.times.partition_point(|&t| t <= t_end);
+            self.slice(start..end.max(start))
+        }
+    }
+
+    /// Iterator over the (time, state) pairs of a Trajectory
+    pub struct Points<'a> {
+        trajectory: &'a Trajectory,
+        index: usize
+    }
+
+    impl Iterator for Points<'_> {
+        type Item = (f64, Vec<f64>);
+
+        fn next(&mut self) -> Option<(f64, Vec<f64>)> {
+            if self.index >= self.trajectory.len() {
+                return None;
+            }
+            let item: (f64, Vec<f64>) = (self.trajectory.times[self.index], self.trajectory.point(self.index));
+            self.index += 1;
+            Some(item)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let remaining: usize = self.trajectory.len() - self.index;
+            (remaining, Some(remaining))
+        }
+    }
+
+    impl ExactSizeIterator for Points<'_> {}
+
+    /// Converts to the (x, y, z) form returned by the `simulate` functions, taking the first three state variables
+    impl From<Trajectory> for (Vec<f64>, Vec<f64>, Vec<f64>) {
+        fn from(trajectory: Trajectory) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+            let mut columns = trajectory.columns.into_iter();
+            (columns.next().unwrap_or_default(), columns.next().unwrap_or_default(), columns.next().unwrap_or_default())
+        }
+    }
+
+    /// Converts to the (theta_1, theta_2) form returned by `DoublePendulum::simulate`, taking the first two state variables
+    impl From<Trajectory> for (Vec<f64>, Vec<f64>) {
+        fn from(trajectory: Trajectory) -> (Vec<f64>, Vec<f64>) {
+            let mut columns = trajectory.columns.into_iter();
+            (columns.next().unwrap_or_default(), columns.next().unwrap_or_default())
+        }
+    }
+
+    /// Selects which points of a fixed step simulation are kept: an initial transient is
+    /// discarded and only every `stride`-th point after it is recorded
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Sampling {
+        /// Duration at the start of the simulation whose points are discarded
+        pub transient: f64,
+        /// Number of steps between kept points
+        pub stride: usize
+    }
+
+    impl Default for Sampling {
+        fn default() -> Sampling {
+            Sampling {
+                transient: 0.0,
+                stride: 1
+            }
+        }
+    }
+
+    impl Sampling {
+        /// Creates a Sampling
+        ///
+        /// *Params
+        ///
+        /// transient: duration at the start of the simulation whose points are discarded
+        ///
+        /// stride: keep every stride-th point after the transient
+        pub fn new(transient: f64, stride: usize) -> Sampling {
+            Sampling::default().with_transient(transient).with_stride(stride)
+        }
+
+        /// Sets the discarded initial duration
+        pub fn with_transient(mut self, transient: f64) -> Sampling {
+            assert!(transient >= 0.0, "transient must not be negative");
+            self.transient = transient;
+            self
+        }
+
+        /// Sets the number of steps between kept points
+        pub fn with_stride(mut self, stride: usize) -> Sampling {
+            assert!(stride > 0, "stride must be at least 1");
+            self.stride = stride;
+            self
+        }
+
+        /// Returns the number of steps of size dt covered by the transient
+        pub fn transient_steps(&self, dt: f64) -> usize {
+            (self.transient / dt - 1e-9).ceil().max(0.0) as usize
+        }
+
+        /// Returns whether the point before the given step is kept
+        pub fn keeps(&self, step: usize, dt: f64) -> bool {
+            let transient_steps: usize = self.transient_steps(dt);
+            step >= transient_steps && (step - transient_steps).is_multiple_of(self.stride)
+        }
+
+        /// Returns the step at which the index-th kept point was recorded
+        pub fn step_of(&self, index: usize, dt: f64) -> usize {
+            self.transient_steps(dt) + index * self.stride
+        }
+    }
+
+    impl Checkpointable for Sampling {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("Sampling");
+            checkpoint.put("transient", self.transient);
+            checkpoint.put("stride", self.stride);
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<Sampling, CheckpointError> {
+            checkpoint.expect_kind("Sampling")?;
+            // the same values the builders reject, since a zero stride never advances past its first point
+            let transient: f64 = checkpoint.get("transient")?;
+            if transient.is_nan() || transient < 0.0 {
+                return Err(CheckpointError::Invalid {
+                    key: "transient".to_string(),
+                    value: checkpoint.raw("transient")?.to_string()
+                });
+            }
+            let stride: usize = checkpoint.get("stride")?;
+            if stride == 0 {
+                return Err(CheckpointError::Invalid {
+                    key: "stride".to_string(),
+                    value: checkpoint.raw("stride")?.to_string()
+                });
+            }
+            Ok(Sampling { transient, stride })
+        }
+    }
+
+    /// Lazily integrated trajectory that yields one (time, state) point at a time, so that
+    /// arbitrarily long runs can be folded or written out in constant memory.
+    ///
+    /// When the system is `Checkpointable` the stream is too, and a stream restored from a
+    /// checkpoint yields exactly the points the original would have yielded after the save
+    pub struct TrajectoryStream<S: DynamicalSystem> {
+        system: S,
+        integrator: Integrator,
+        dt: f64,
+        t: f64,
+        state: Vec<f64>,
+        steps_taken: usize,
+        step_limit: Option<usize>,
+        sampling: Sampling
+    }
+
+    impl<S: DynamicalSystem> TrajectoryStream<S> {
+        /// Creates an unbounded TrajectoryStream starting from the initial state of a system at t = 0
+        ///
+        /// *Params
+        ///
+        /// system: system to integrate. Pass a reference to stream a system without moving it
+        ///
+        /// integrator: scheme used for each step
+        ///
+        /// dt: size of each step
+        pub fn new(system: S, integrator: Integrator, dt: f64) -> TrajectoryStream<S> {
+            let state: Vec<f64> = system.initial_state();
+            TrajectoryStream {
+                system,
+                integrator,
+                dt,
+                t: 0.0,
+                state,
+                steps_taken: 0,
+                step_limit: None,
+                sampling: Sampling::default()
+            }
+        }
+
+        /// Starts the stream from the given state instead of the initial state of the system
+        pub fn with_initial_state(mut self, state: Vec<f64>) -> TrajectoryStream<S> {
+            assert_eq!(state.len(), self.system.dimension(), "state does not match the system dimension");
+            self.state = state;
+            self
+        }
+
+        /// Ends the stream after the given number of integration steps, including skipped ones
+        pub fn with_steps(mut self, steps: usize) -> TrajectoryStream<S> {
+            self.step_limit = Some(steps);
+            self
+        }
+
+        /// Yields only every k-th point
+        pub fn with_stride(mut self, stride: usize) -> TrajectoryStream<S> {
+            self.sampling = self.sampling.with_stride(stride);
+            self
+        }
+
+        /// Skips the points with times before the given duration
+        pub fn with_transient(mut self, duration: f64) -> TrajectoryStream<S> {
+            self.sampling = self.sampling.with_transient(duration);
+            self
+        }
+
+        /// Replaces both the transient and the stride
+        pub fn with_sampling(mut self, sampling: Sampling) -> TrajectoryStream<S> {
+            self.sampling = sampling;
+            self
+        }
+
+        /// Returns the system being integrated
+        pub fn system(&self) -> &S {
+            &self.system
+        }
+
+        /// Returns the time of the next point
+        pub fn time(&self) -> f64 {
+            self.t
+        }
+
+        /// Returns the state of the next point
+        pub fn state(&self) -> &[f64] {
+            &self.state
+        }
+
+        /// Returns the number of integration steps taken so far
+        pub fn steps_taken(&self) -> usize {
+            self.steps_taken
+        }
+
+        /// Collects the remaining points into a Trajectory
+        pub fn collect_trajectory(self) -> Trajectory {
+            let mut trajectory: Trajectory = Trajectory::for_system(&self.system);
+            for (t, state) in self {
+                trajectory.push(t, &state);
+            }
+            trajectory
+        }
+    }
+
+    impl<S: DynamicalSystem> Iterator for TrajectoryStream<S> {
+        type Item = (f64, Vec<f64>);
+
+        fn next(&mut self) -> Option<(f64, Vec<f64>)> {
+            loop {
+                if self.step_limit.is_some_and(|limit| self.steps_taken >= limit) {
+                    return None;
+                }
+
+                let emit: bool = self.sampling.keeps(self.steps_taken, self.dt);
+
+                let next_state: Vec<f64> = self.integrator.step(&self.system, self.t, &self.state, self.dt);
+                let t: f64 = self.t;
+                let state: Vec<f64> = std::mem::replace(&mut self.state, next_state);
+                self.steps_taken += 1;
+                self.t = self.steps_taken as f64 * self.dt;
+
+                if emit {
+                    return Some((t, state));
+                }
+            }
+        }
+    }
+
+    impl<S: DynamicalSystem + Checkpointable> Checkpointable for TrajectoryStream<S> {
+        fn to_checkpoint(&self) -> Checkpoint {
+            let mut checkpoint: Checkpoint = Checkpoint::new("TrajectoryStream");
+            checkpoint.put_section("system", &self.system.to_checkpoint());
+            checkpoint.put_section("integrator", &self.integrator.to_checkpoint());
+            checkpoint.put("dt", self.dt);
+            checkpoint.put("t", self.t);
+            checkpoint.put_list("state", &self.state);
+            checkpoint.put("steps_taken", self.steps_taken);
+            checkpoint.put_optional("step_limit", self.step_limit);
+            checkpoint.put_section("sampling", &self.sampling.to_checkpoint());
+            checkpoint
+        }
+
+        fn from_checkpoint(checkpoint: &Checkpoint) -> Result<TrajectoryStream<S>, CheckpointError> {
+            checkpoint.expect_kind("TrajectoryStream")?;
+            Ok(TrajectoryStream {
+                system: S::from_checkpoint(&checkpoint.section("system"))?,
+                integrator: Integrator::from_checkpoint(&checkpoint.section("integrator"))?,
+                dt: checkpoint.get("dt")?,
+                t: checkpoint.get("t")?,
+                state: checkpoint.get_list("state")?,
+                steps_taken: checkpoint.get("steps_taken")?,
+                step_limit: checkpoint.get_optional("step_limit")?,
+                sampling: Sampling::from_checkpoint(&checkpoint.section("sampling"))?
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::lorenz::lorenz::LorenzSystem;
+
+        #[test]
+        fn resumed_stream_continues_bit_for_bit() {
+            let lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 0);
+            let uninterrupted: Vec<(f64, Vec<f64>)> = TrajectoryStream::new(lorenz.clone(), Integrator::RungeKutta4, 0.01)
+                .with_steps(1000)
+                .with_stride(3)
+                .collect();
+
+            let mut stream: TrajectoryStream<LorenzSystem> = TrajectoryStream::new(lorenz, Integrator::RungeKutta4, 0.01).with_steps(1000).with_stride(3);
+            let mut resumed: Vec<(f64, Vec<f64>)> = stream.by_ref().take(100).collect();
+            let path: std::path::PathBuf = std::env::temp_dir().join(format!("chaos_theory_stream_{}.checkpoint", std::process::id()));
+            stream.save(&path).unwrap();
+            let restored: TrajectoryStream<LorenzSystem> = TrajectoryStream::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            resumed.extend(restored);
+            assert_eq!(resumed, uninterrupted);
+        }
+
+        #[test]
+        fn sampling_the_builders_reject_is_not_restored() {
+            for (key, value) in [("stride", "0"), ("transient", "-1"), ("transient", "NaN")] {
+                let mut checkpoint: Checkpoint = Sampling::new(1.0, 2).to_checkpoint();
+                checkpoint.put(key, value);
+                match Sampling::from_checkpoint(&checkpoint) {
+                    Err(CheckpointError::Invalid { key: invalid, value: raw }) => assert_eq!((invalid.as_str(), raw.as_str()), (key, value)),
+                    other => panic!("{} = {} restored as {:?}", key, value, other)
+                }
+            }
+            assert_eq!(Sampling::from_checkpoint(&Sampling::new(1.0, 2).to_checkpoint()).unwrap(), Sampling::new(1.0, 2));
+
+            // an unbounded stream restored with a zero stride would never yield its second point
+            let lorenz: LorenzSystem = LorenzSystem::new(1.0, 1.0, 1.0, 10.0, 28.0, 8.0 / 3.0, 0.01, 0);
+            let mut checkpoint: Checkpoint = TrajectoryStream::new(lorenz, Integrator::RungeKutta4, 0.01).to_checkpoint();
+            checkpoint.put("sampling.stride", 0);
+            assert!(TrajectoryStream::<LorenzSystem>::from_checkpoint(&checkpoint).is_err());
+        }
+    }
+}
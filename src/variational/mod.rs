@@ -0,0 +1,168 @@
+pub mod variational {
+    use crate::dynamical_system::dynamical_system::DynamicalSystem;
+    use crate::integrators::integrators::Integrator;
+
+    /// A system extended with its variational equations dv/dt = J(t, x) v for a set of tangent vectors v.
+    ///
+    /// The extended state is laid out as [x, v_1, ..., v_k], each block of length n, so the extended
+    /// system can be integrated with any of the crate's solvers
+    #[derive(Debug, Clone)]
+    pub struct VariationalSystem<S: DynamicalSystem> {
+        system: S,
+        initial_tangent_vectors: Vec<Vec<f64>>
+    }
+
+    impl<S: DynamicalSystem> VariationalSystem<S> {
+        /// Creates a VariationalSystem whose tangent vectors start as the columns of the identity,
+        /// so that they evolve into the columns of the fundamental matrix of the linearized flow
+        pub fn new(system: S) -> VariationalSystem<S> {
+            let n: usize = system.dimension();
+            let identity: Vec<Vec<f64>> = (0..n).map(|j| (0..n).map(|i| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+            VariationalSystem {
+                system,
+                initial_tangent_vectors: identity
+            }
+        }
+
+        /// Replaces the initial tangent vectors, each of length `dimension()` of the underlying system
+        pub fn with_tangent_vectors(mut self, tangent_vectors: Vec<Vec<f64>>) -> VariationalSystem<S> {
+            let n: usize = self.system.dimension();
+            assert!(tangent_vectors.iter().all(|v| v.len() == n), "tangent vectors must match the system dimension");
+            self.initial_tangent_vectors = tangent_vectors;
+            self
+        }
+
+        /// Returns the underlying system
+        pub fn system(&self) -> &S {
+            &self.system
+        }
+
+        /// Returns the number of tangent vectors carried along
+        pub fn tangent_count(&self) -> usize {
+            self.initial_tangent_vectors.len()
+        }
+
+        /// Splits an extended state into the state of the underlying system and the tangent vectors
+        pub fn split(&self, extended: &[f64]) -> (Vec<f64>, Vec<Vec<f64>>) {
+            let n: usize = self.system.dimension();
+            let state: Vec<f64> = extended[..n].to_vec();
+            let tangent_vectors: Vec<Vec<f64>> = extended[n..].chunks(n).map(|v| v.to_vec()).collect();
+            (state, tangent_vectors)
+        }
+
+        /// Builds an extended state from a state of the underlying system and tangent vectors
+        pub fn combine(&self, state: &[f64], tangent_vectors: &[Vec<f64>]) -> Vec<f64> {
+            let mut extended: Vec<f64> = state.to_vec();
+            for v in tangent_vectors {
+                extended.extend_from_slice(v);
+            }
+            extended
+        }
+    }
+
+    impl<S: DynamicalSystem> DynamicalSystem for VariationalSystem<S> {
+        fn dimension(&self) -> usize {
+            self.system.dimension() * (1 + self.tangent_count())
+        }
+
+        fn parameters(&self) -> Vec<(&'static str, f64)> {
+            self.system.parameters()
+        }
+
+        fn variable_names(&self) -> Vec<String> {
+            let names: Vec<String> = self.system.variable_names();
+            let mut extended: Vec<String> = names.clone();
+            for j in 0..self.tangent_count() {
+                extended.extend(names.iter().map(|name| format!("d{}_{}", name, j)));
+            }
+            extended
+        }
+
+        fn initial_state(&self) -> Vec<f64> {
+            self.combine(&self.system.initial_state(), &self.initial_tangent_vectors)
+        }
+
+        fn derivative(&self, t: f64, extended: &[f64]) -> Vec<f64> {
+            let n: usize = self.system.dimension();
+            let state: &[f64] = &extended[..n];
+            let jacobian: Vec<Vec<f64>> = self.system.jacobian(t, state);
+
+            let mut derivative: Vec<f64> = self.system.derivative(t, state);
+            for v in extended[n..].chunks(n) {
+                derivative.extend(jacobian.iter().map(|row| row.iter().zip(v.iter()).map(|(a, b)| a * b).sum::<f64>()));
+            }
+            derivative
+        }
+    }
+
+    /// Integrates a system and its tangent vectors together with a fixed step scheme, exposing both as they evolve
+    pub struct TangentFlow<S: DynamicalSystem> {
+        variational: VariationalSystem<S>,
+        integrator: Integrator,
+        dt: f64,
+        t: f64,
+        extended: Vec<f64>
+    }
+
+    impl<S: DynamicalSystem> TangentFlow<S> {
+        /// Creates a TangentFlow starting at t = 0 from the initial state of the variational system
+        ///
+        /// *Params
+        ///
+        /// variational: system extended with its tangent vectors
+        ///
+        /// integrator: scheme used for each step
+        ///
+        /// dt: size of each step
+        pub fn new(variational: VariationalSystem<S>, integrator: Integrator, dt: f64) -> TangentFlow<S> {
+            let extended: Vec<f64> = variational.initial_state();
+            TangentFlow {
+                variational,
+                integrator,
+                dt,
+                t: 0.0,
+                extended
+            }
+        }
+
+        /// Advances the state and the tangent vectors by a single step
+        pub fn step(&mut self) {
+            self.extended = self.integrator.step(&self.variational, self.t, &self.extended, self.dt);
+            self.t += self.dt;
+        }
+
+        /// Returns the current time
+        pub fn time(&self) -> f64 {
+            self.t
+        }
+
+        /// Returns the step size
+        pub fn dt(&self) -> f64 {
+            self.dt
+        }
+
+        /// Returns the current state of the underlying system
+        pub fn state(&self) -> Vec<f64> {
+            self.extended[..self.variational.system().dimension()].to_vec()
+        }
+
+        /// Returns the current tangent vectors
+        pub fn tangent_vectors(&self) -> Vec<Vec<f64>> {
+            self.variational.split(&self.extended).1
+        }
+
+        /// Returns the current tangent vectors as the columns of a matrix, given as rows
+        pub fn tangent_matrix(&self) -> Vec<Vec<f64>> {
+            let tangent_vectors: Vec<Vec<f64>> = self.tangent_vectors();
+            (0..self.variational.system().dimension())
+                .map(|i| tangent_vectors.iter().map(|v| v[i]).collect())
+                .collect()
+        }
+
+        /// Replaces the tangent vectors, for example after renormalizing them
+        pub fn set_tangent_vectors(&mut self, tangent_vectors: &[Vec<f64>]) {
+            let state: Vec<f64> = self.state();
+            self.extended = self.variational.combine(&state, tangent_vectors);
+        }
+    }
+}